@@ -16,6 +16,12 @@ pub const FRAME_SMOOTH_COUNT: usize = 120;
 pub const FRAME_TIME_TARGET: SecondsValue = 1. / 60.;
 pub const LOG_INTERVAL: SecondsValue = 5.0;
 pub const SAVE_INTERVAL: SecondsValue = 300.0;
+pub const STATS_INTERVAL: SecondsValue = 10.0;
+pub const SPECIES_INTERVAL: SecondsValue = 5.0;
+pub const SPECIES_DISTANCE_THRESHOLD: usize = 48;
+pub const INSPECT_TOP: usize = 10;
+/// Smallest family tree worth pruning
+pub const FAMILY_TREE_PRUNE_SIZE: usize = 1000;
 pub const DEAD_ZONE: AxisValue = 0.3f32;
 pub const TURN_SPEED: f32 = consts::PI * 200.;
 pub const DEBUG_DRAW_BRAKE_SCALE: f32 = 0.05;
@@ -25,6 +31,8 @@ pub const MAX_FRAME_LENGTH: SecondsValue = (1.0 / 30.0) as SecondsValue;
 pub const THRUST_POWER: f32 = 5000.;
 pub const POWER_BOOST: f32 = 100.;
 pub const DRAG_COEFFICIENT: f32 = 0.000_001;
+/// Maximum number of bits flipped by a mutation, per byte of DNA
+pub const MUTATION_RATE: f32 = 0.125;
 #[allow(unused)]
 pub const COMPASS_SPRING_POWER: f32 = 1000.0;
 pub const JOINT_UPPER_ANGLE: f32 = consts::PI / 6.;
@@ -53,12 +61,24 @@ pub const MINION_CHARGE_DECAY_TIME: SecondsValue = 0.25;
 pub const PLAYER_CHARGE_DECAY_TIME: SecondsValue = 0.1;
 pub const PLAYER_CHARGE_INITIAL_VALUE: f32 = 25.0;
 pub const PLAYER_CHARGE_REST_VALUE: f32 = 0.05;
+pub const EMITTER_COUNT: usize = 7;
+/// Sides of the polygon approximating the walls of a circular world
+pub const BOUNDARY_CIRCLE_SIDES: usize = 64;
+/// Distance from the edge of a wrapping world within which agents are also
+/// drawn on the opposite side
+pub const BOUNDARY_IMAGE_MARGIN: f32 = 10.;
 pub const EMITTER_DISTANCE: f32 = 40.;
 pub const EMITTER_PERIOD: SecondsValue = 0.2;
 #[allow(unused)]
 pub const EMITTER_SPREAD_ANGLE: f32 = consts::PI / 12.;
 pub const EMITTER_SPREAD_JITTER: f32 = 0.1;
 pub const EMITTER_INTENSITY_DECAY: f32 = 1.0;
+/// Distance from the pointer within which a feeder can be picked
+pub const EMITTER_PICK_RADIUS: f32 = 3.;
+/// Change of the period or power of a feeder, per key press
+pub const EMITTER_ADJUST_FACTOR: f32 = 1.25;
+pub const EMITTER_PERIOD_MIN: SecondsValue = 0.01;
+pub const EMITTER_POWER_MAX: f32 = 10.;
 pub const BULLET_SPEED_SCALE: f32 = 100.;
 pub const BULLET_FIRE_RATE_SCALE: SecondsValue = 0.5;
 pub const BULLET_FULL_CHARGE: SecondsValue = 1.0;
@@ -72,8 +92,11 @@ pub const RESTITUTION_DEFAULT: f32 = 0.6;
 pub const RESTITUTION_PLAYER: f32 = 0.1;
 pub const FRICTION_DEFAULT: f32 = 0.7;
 pub const FRICTION_PLAYER: f32 = 0.6;
+/// Smallest width or height of an obstacle
+pub const PROP_MIN_SIZE: f32 = 0.1;
 pub const B2_LINEAR_SLOP: f32 = 0.005;
 pub const DEFAULT_MINION_GENE_POOL_FILE: &str = "minion_gene_pool.csv";
+pub const SELECTION_TOURNAMENT_SIZE: usize = 3;
 pub const DEFAULT_MINION_GENE_POOL: &[&str] = &[
 	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
 	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
@@ -94,10 +117,15 @@ pub const CONFIG_DIR_SAVED_STATE: &str = "saved_state";
 pub const CONFIG_DIR_RESOURCES: &str = "resources";
 pub const DUMP_FILE_PATTERN_CSV: &str = "%Y%m%d_%H%M%S.csv";
 pub const DUMP_FILE_PATTERN_JSON: &str = "%Y%m%d_%H%M%S.json";
+pub const DUMP_FILE_PATTERN_CBOR: &str = "%Y%m%d_%H%M%S.cbor";
+pub const DUMP_FILE_PATTERN_CBOR_GZ: &str = "%Y%m%d_%H%M%S.cbor.gz";
 
 pub const CAPTURE_FOLDER_TIMESTAMP_PATTERN: &str = "%Y%m%d_%H%M%S";
 pub const CAPTURE_FOLDER: &str = "capture";
 pub const CAPTURE_FILENAME_PREFIX: &str = "capture_";
+pub const TIMELAPSE_INTERVAL: usize = 600;
+pub const TIMELAPSE_SIZE: u32 = 1024;
+pub const CONFIG_POLL_INTERVAL: SecondsValue = 1.0;
 
 pub const AMBIENT_LIGHTS: &[[f32; 4]] = &[
 	[1.0, 1.0, 1.0, 1.0],
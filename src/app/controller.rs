@@ -32,6 +32,7 @@ const KEY_PRESSED_ONCE_MAP: &[(input::Key, Event)] = &[
 	(F6, SaveGenePoolToFile),
 	(F7, SaveWorldToFile),
 	(F8, RestartFromCheckpoint),
+	(F9, ToggleSpeciesTint),
 	(F10, ToggleDebug),
 	(F12, ToggleCapture),
 	(GamepadStart, ToggleDebug),
@@ -97,14 +98,41 @@ impl DefaultController {
 		};
 
 		if input_state.key_once(MouseMiddle) {
-			if input_state.any_ctrl_pressed() {
+			if input_state.any_shift_pressed() {
+				events.push(Event::AddFeeder(mouse_world_pos));
+			} else if input_state.any_ctrl_pressed() {
 				events.push(Event::RandomizeMinion(mouse_world_pos));
 			} else {
 				events.push(Event::NewMinion(mouse_world_pos));
 			}
 		}
 
+		// feeder tools act on the feeder under the pointer
+		if input_state.key_once(Del) {
+			events.push(Event::RemoveFeeder(mouse_world_pos));
+		}
+		for (key, event) in &[
+			(OpenBracket, Event::ScaleFeederRate(mouse_world_pos, EMITTER_ADJUST_FACTOR)),
+			(CloseBracket, Event::ScaleFeederRate(mouse_world_pos, 1. / EMITTER_ADJUST_FACTOR)),
+			(Semicolon, Event::ScaleFeederPower(mouse_world_pos, 1. / EMITTER_ADJUST_FACTOR)),
+			(Apostrophe, Event::ScaleFeederPower(mouse_world_pos, EMITTER_ADJUST_FACTOR)),
+		] {
+			if input_state.key_once(*key) {
+				events.push(*event);
+			}
+		}
+
 		match input_state.dragging() {
+			// Shift + RMB drags feeders instead of the camera
+			input::Dragging::Begin(_, from) if input_state.any_shift_pressed() => {
+				events.push(Event::BeginFeederDrag(world_transform.to_world(from)));
+			}
+			input::Dragging::Dragging(_, _, to) if input_state.any_shift_pressed() => {
+				events.push(Event::FeederDrag(world_transform.to_world(to)));
+			}
+			input::Dragging::End(_, _, to, _) if input_state.any_shift_pressed() => {
+				events.push(Event::EndFeederDrag(world_transform.to_world(to)));
+			}
 			input::Dragging::Begin(_, from) => {
 				let from = world_transform.to_world(from);
 				events.push(Event::BeginDrag(from, from));
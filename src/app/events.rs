@@ -1,7 +1,7 @@
 use core::clock::*;
 use core::geometry::*;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum VectorDirection {
 	None,
 	Orientation(Position),
@@ -10,7 +10,7 @@ pub enum VectorDirection {
 	FromVelocity,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Event {
 	CamUp(f32),
 	CamDown(f32),
@@ -41,6 +41,7 @@ pub enum Event {
 	SaveWorldToFile,
 	RestartFromCheckpoint,
 	ToggleDebug,
+	ToggleSpeciesTint,
 
 	TogglePause,
 	ToggleGui,
@@ -58,4 +59,14 @@ pub enum Event {
 	BeginDrag(Position, Position),
 	Drag(Position, Position),
 	EndDrag(Position, Position, Velocity),
+
+	AddFeeder(Position),
+	RemoveFeeder(Position),
+	BeginFeederDrag(Position),
+	FeederDrag(Position),
+	EndFeederDrag(Position),
+	/// Multiplies the period of the feeder at a position
+	ScaleFeederRate(Position, f32),
+	/// Multiplies the power of the feeder at a position
+	ScaleFeederPower(Position, f32),
 }
@@ -0,0 +1,176 @@
+//! Recording of the events which drive a session, and their replay
+//!
+//! A journal is a JSON Lines file. The first entry holds the random seed, the
+//! system mode and a snapshot of the world at the start of the session; it is
+//! followed by the events sent to the app, the configurations reloaded while
+//! it ran and the length of each simulation step, in the order in which they
+//! happened.
+use app::events::Event;
+use backend::config::SimulationConfig;
+use backend::systems::SystemMode;
+use backend::world;
+use backend::world::persist;
+use core::clock::{seconds, Seconds, SecondsValue};
+use core::rng::Seed;
+use serde_json;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::path;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Entry {
+	Start {
+		seed: Seed,
+		mode: SystemMode,
+		snapshot: serde_json::Value,
+	},
+	/// Sent before the step with the same number
+	Event {
+		step: usize,
+		event: Event,
+	},
+	/// A configuration reloaded before the step with the same number
+	Config {
+		step: usize,
+		config: SimulationConfig,
+	},
+	Step {
+		step: usize,
+		dt: SecondsValue,
+	},
+}
+
+pub struct JournalWriter {
+	out: Box<dyn io::Write>,
+}
+
+impl JournalWriter {
+	pub fn new<W>(out: W) -> Self
+	where W: io::Write + 'static {
+		JournalWriter { out: Box::new(out) }
+	}
+
+	pub fn create(file_path: &path::Path) -> io::Result<Self> {
+		Ok(Self::new(io::BufWriter::new(fs::File::create(file_path)?)))
+	}
+
+	pub fn start(&mut self, world: &world::World, mode: SystemMode) -> io::Result<()> {
+		let snapshot = serde_json::to_value(persist::Serializer::save_snapshot(world))?;
+		self.write(&Entry::Start { seed: world.seed(), mode, snapshot })
+	}
+
+	pub fn event(&mut self, step: usize, event: Event) -> io::Result<()> { self.write(&Entry::Event { step, event }) }
+
+	pub fn config(&mut self, step: usize, config: &SimulationConfig) -> io::Result<()> {
+		self.write(&Entry::Config { step, config: config.clone() })
+	}
+
+	pub fn step(&mut self, step: usize, dt: Seconds) -> io::Result<()> {
+		self.write(&Entry::Step { step, dt: dt.get() })
+	}
+
+	fn write(&mut self, entry: &Entry) -> io::Result<()> {
+		serde_json::to_writer(&mut self.out, entry)?;
+		writeln!(self.out)
+	}
+}
+
+pub struct Replay {
+	seed: Seed,
+	mode: SystemMode,
+	snapshot: Option<persist::World>,
+	lines: io::Lines<io::BufReader<fs::File>>,
+	next: Option<Entry>,
+}
+
+impl Replay {
+	/// Reads the start of the journal. The rest is read as the replay goes on
+	pub fn open(file_path: &path::Path) -> io::Result<Self> {
+		let mut lines = io::BufReader::new(fs::File::open(file_path)?).lines();
+		let first = match lines.next() {
+			Some(line) => serde_json::from_str(&line?)?,
+			None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Empty journal")),
+		};
+		match first {
+			Entry::Start { seed, mode, snapshot } => {
+				let snapshot = persist::Serializer::migrate(snapshot)?;
+				let mut replay = Replay { seed, mode, snapshot: Some(snapshot), lines, next: None };
+				replay.advance();
+				Ok(replay)
+			}
+			_ => Err(io::Error::new(io::ErrorKind::InvalidData, "The journal does not begin with a start entry")),
+		}
+	}
+
+	pub fn seed(&self) -> Seed { self.seed }
+
+	pub fn mode(&self) -> SystemMode { self.mode }
+
+	/// Restores the world to the state it was in when the recording started.
+	/// Only the first call has any effect
+	pub fn restore(&mut self, world: &mut world::World) {
+		if let Some(snapshot) = self.snapshot.take() {
+			persist::Serializer::restore_snapshot(&snapshot, world);
+		}
+	}
+
+	/// The events sent before the given step
+	pub fn events(&mut self, step: usize) -> Vec<Event> {
+		let mut events = Vec::new();
+		while let Some(Entry::Event { step: event_step, event }) = self.next {
+			if event_step > step {
+				break;
+			}
+			events.push(event);
+			self.advance();
+		}
+		events
+	}
+
+	/// The last configuration reloaded before the given step, if any
+	pub fn config(&mut self, step: usize) -> Option<SimulationConfig> {
+		let mut config = None;
+		while let Some(Entry::Config { step: config_step, .. }) = self.next {
+			if config_step > step {
+				break;
+			}
+			if let Some(Entry::Config { config: reloaded, .. }) = self.next.take() {
+				config = Some(reloaded);
+			}
+			self.advance();
+		}
+		config
+	}
+
+	/// The length of the given step, if it was recorded
+	pub fn dt(&mut self, step: usize) -> Option<Seconds> {
+		match self.next {
+			Some(Entry::Step { step: recorded_step, dt }) if recorded_step == step => {
+				self.advance();
+				Some(seconds(dt))
+			}
+			_ => None,
+		}
+	}
+
+	pub fn is_finished(&self) -> bool { self.next.is_none() }
+
+	fn advance(&mut self) {
+		self.next = match self.lines.next() {
+			Some(Ok(line)) => match serde_json::from_str(&line) {
+				Ok(entry) => Some(entry),
+				Err(e) => {
+					error!("Invalid journal entry, the replay ends here: {}", e);
+					None
+				}
+			},
+			Some(Err(e)) => {
+				error!("Unable to read the journal, the replay ends here: {}", e);
+				None
+			}
+			None => None,
+		}
+	}
+}
@@ -0,0 +1,193 @@
+use app::SimulationUpdate;
+use core::clock::{Seconds, SecondsValue};
+use std::fmt;
+
+/// Conditions which end a headless run. Unset limits never trigger.
+/// Extinctions are counted from the start of the run, leaving out those of a
+/// resumed snapshot
+#[derive(Clone, Debug, Default)]
+pub struct RunLimits {
+	pub steps: Option<usize>,
+	pub simulation_time: Option<SecondsValue>,
+	pub wall_clock_time: Option<SecondsValue>,
+	pub stop_on_extinction: bool,
+	pub min_population: Option<usize>,
+	pub max_population: Option<usize>,
+	pub max_extinctions: Option<usize>,
+}
+
+/// Progress of a run since it started
+#[derive(Clone, Debug, Default)]
+pub struct RunProgress {
+	pub steps: usize,
+	pub simulation_time: Seconds,
+	pub wall_clock_time: Seconds,
+	pub population: usize,
+	/// Since the start of the run
+	pub extinctions: usize,
+	/// Including those of a resumed snapshot
+	pub total_extinctions: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+	Quit,
+	Interrupted,
+	Steps,
+	SimulationTime,
+	WallClockTime,
+	Extinction,
+	PopulationBelow,
+	PopulationAbove,
+	Extinctions,
+	EndOfReplay,
+}
+
+impl StopReason {
+	pub fn exit_code(self) -> i32 {
+		match self {
+			StopReason::Quit | StopReason::EndOfReplay => 0,
+			StopReason::Steps => 10,
+			StopReason::SimulationTime => 11,
+			StopReason::WallClockTime => 12,
+			StopReason::Extinction => 20,
+			StopReason::PopulationBelow => 21,
+			StopReason::PopulationAbove => 22,
+			StopReason::Extinctions => 23,
+			StopReason::Interrupted => 130,
+		}
+	}
+}
+
+impl fmt::Display for StopReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let text = match *self {
+			StopReason::Quit => "quit",
+			StopReason::Interrupted => "interrupted",
+			StopReason::Steps => "step limit reached",
+			StopReason::SimulationTime => "simulation time limit reached",
+			StopReason::WallClockTime => "wall clock time limit reached",
+			StopReason::Extinction => "extinction",
+			StopReason::PopulationBelow => "population below minimum",
+			StopReason::PopulationAbove => "population above maximum",
+			StopReason::Extinctions => "extinction limit reached",
+			StopReason::EndOfReplay => "end of replay",
+		};
+		f.write_str(text)
+	}
+}
+
+impl RunProgress {
+	pub fn update(&mut self, simulation_update: &SimulationUpdate, initial_extinctions: usize) {
+		self.steps += 1;
+		self.simulation_time += simulation_update.dt;
+		self.population = simulation_update.population;
+		self.total_extinctions = simulation_update.extinctions;
+		self.extinctions = simulation_update.extinctions.saturating_sub(initial_extinctions);
+	}
+}
+
+impl RunLimits {
+	pub fn check(&self, progress: &RunProgress) -> Option<StopReason> {
+		fn exceeds<T: PartialOrd>(limit: Option<T>, value: T) -> bool { limit.map(|l| value >= l).unwrap_or(false) }
+
+		if self.stop_on_extinction && progress.extinctions > 0 {
+			Some(StopReason::Extinction)
+		} else if exceeds(self.max_extinctions, progress.extinctions) {
+			Some(StopReason::Extinctions)
+		} else if self.min_population.map(|n| progress.population < n).unwrap_or(false) {
+			Some(StopReason::PopulationBelow)
+		} else if self.max_population.map(|n| progress.population > n).unwrap_or(false) {
+			Some(StopReason::PopulationAbove)
+		} else if exceeds(self.steps, progress.steps) {
+			Some(StopReason::Steps)
+		} else if exceeds(self.simulation_time, progress.simulation_time.get()) {
+			Some(StopReason::SimulationTime)
+		} else if exceeds(self.wall_clock_time, progress.wall_clock_time.get()) {
+			Some(StopReason::WallClockTime)
+		} else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::world::environment::Schedule;
+	use core::clock::seconds;
+
+	fn progress(steps: usize, simulation_time: SecondsValue, population: usize, extinctions: usize) -> RunProgress {
+		RunProgress {
+			steps,
+			simulation_time: seconds(simulation_time),
+			wall_clock_time: seconds(simulation_time / 10.),
+			population,
+			extinctions,
+			total_extinctions: extinctions + 5,
+		}
+	}
+
+	#[test]
+	fn check_every_limit() {
+		let limits = RunLimits {
+			steps: Some(100),
+			simulation_time: Some(60.),
+			wall_clock_time: Some(3.),
+			stop_on_extinction: false,
+			min_population: Some(2),
+			max_population: Some(50),
+			max_extinctions: Some(3),
+		};
+		let table = [
+			(progress(10, 1., 10, 0), None),
+			(progress(100, 1., 10, 0), Some((StopReason::Steps, 10))),
+			(progress(10, 60., 10, 0), Some((StopReason::SimulationTime, 11))),
+			(progress(10, 40., 10, 0), Some((StopReason::WallClockTime, 12))),
+			(progress(10, 1., 1, 0), Some((StopReason::PopulationBelow, 21))),
+			(progress(10, 1., 51, 0), Some((StopReason::PopulationAbove, 22))),
+			(progress(10, 1., 10, 2), None),
+			(progress(10, 1., 10, 3), Some((StopReason::Extinctions, 23))),
+			// the population limits come first
+			(progress(100, 60., 0, 0), Some((StopReason::PopulationBelow, 21))),
+		];
+		for (progress, expected) in table.iter() {
+			let reason = limits.check(progress);
+			assert_eq!(reason.map(|r| (r, r.exit_code())), *expected, "{:?}", progress);
+		}
+	}
+
+	#[test]
+	fn stop_on_extinction() {
+		let limits = RunLimits { stop_on_extinction: true, max_extinctions: Some(3), ..Default::default() };
+		assert_eq!(limits.check(&progress(10, 1., 10, 1)), Some(StopReason::Extinction));
+		assert_eq!(StopReason::Extinction.exit_code(), 20);
+		assert_eq!(limits.check(&progress(10, 1., 10, 0)), None);
+	}
+
+	#[test]
+	fn extinctions_are_counted_from_the_start() {
+		let update = |extinctions| SimulationUpdate {
+			timestamp: seconds(0.),
+			dt: seconds(0.5),
+			count: 1,
+			elapsed: seconds(0.5),
+			population: 10,
+			extinctions,
+			species: Vec::new(),
+			conditions: Schedule::default().world_conditions(0.),
+		};
+		// resumed from a snapshot with 5 extinctions
+		let mut progress = RunProgress::default();
+		progress.update(&update(5), 5);
+		assert_eq!((progress.extinctions, progress.total_extinctions), (0, 5));
+		let limits = RunLimits { stop_on_extinction: true, max_extinctions: Some(2), ..Default::default() };
+		assert_eq!(limits.check(&progress), None);
+		progress.update(&update(7), 5);
+		assert_eq!((progress.steps, progress.extinctions, progress.total_extinctions), (2, 2, 7));
+		assert_eq!(limits.check(&progress), Some(StopReason::Extinction));
+		let limits = RunLimits { max_extinctions: Some(2), ..Default::default() };
+		assert_eq!(limits.check(&progress), Some(StopReason::Extinctions));
+		assert_eq!(RunLimits::default().check(&progress), None);
+	}
+}
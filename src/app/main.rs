@@ -1,27 +1,43 @@
+#[cfg(feature = "audio")]
 use frontend::audio::{self, SoundSystem};
+#[cfg(feature = "graphics")]
 use frontend::gfx_window_glutin;
+#[cfg(feature = "graphics")]
 use frontend::input::EventMapper;
+#[cfg(all(feature = "graphics", feature = "gamepad"))]
 use frontend::input::GamepadEventLoop;
+#[cfg(feature = "graphics")]
 use frontend::render;
+#[cfg(feature = "graphics")]
 use frontend::render::{formats, Overlay, Renderer};
 use frontend::ui;
+use std::io;
 use std::path;
+use std::process;
 
+#[cfg(feature = "graphics")]
 use conrod;
 
-use core::clock::{seconds, Hourglass, SecondsValue, SystemTimer};
+use core::clock::{seconds, Hourglass, SecondsValue, SystemTimer, Timer};
+#[cfg(feature = "graphics")]
 use core::math::Directional;
 use core::resource::filesystem::ResourceLoader;
 use core::resource::filesystem::ResourceLoaderBuilder;
+use core::rng::Seed;
 use ctrlc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use app;
+#[cfg(feature = "graphics")]
 use app::capture::Capture;
 use app::constants::*;
+use app::{RunLimits, RunOptions, RunProgress, StopReason};
+#[cfg(feature = "graphics")]
 use glutin;
+#[cfg(feature = "graphics")]
 use glutin::GlContext;
+#[cfg(feature = "graphics")]
 use winit::{self, KeyboardInput, VirtualKeyCode, WindowEvent};
 
 pub fn make_resource_loader(config_home: &path::Path) -> ResourceLoader {
@@ -34,16 +50,27 @@ pub fn make_resource_loader(config_home: &path::Path) -> ResourceLoader {
 		.build()
 }
 
+fn exit_with_error(e: &io::Error) -> ! {
+	eprintln!("{}", e);
+	eprintln!("Use -n to start from a new population, or -i to pick another snapshot");
+	process::exit(1)
+}
+
+#[cfg(feature = "graphics")]
+#[allow(clippy::too_many_arguments)]
 pub fn main_loop(
 	minion_gene_pool: &str,
 	config_home: path::PathBuf,
 	world_file: Option<path::PathBuf>,
+	seed: Option<Seed>,
+	run_options: RunOptions,
 	fullscreen: Option<usize>,
 	width: Option<u32>,
 	height: Option<u32>,
 	audio_device: Option<usize>,
 ) {
 	let mut events_loop = winit::EventsLoop::new();
+	#[cfg(feature = "gamepad")]
 	let mut maybe_gamepad = GamepadEventLoop::new();
 
 	let builder = winit::WindowBuilder::new().with_title("Rust-oids".to_string());
@@ -73,22 +100,46 @@ pub fn main_loop(
 	let mapper = app::WinitEventMapper::new();
 
 	// Create a new game and run it.
-	let mut app =
-		app::App::new(u32::from(w), u32::from(h), VIEW_SCALE_BASE, config_home, &res, minion_gene_pool, world_file);
+	let mut app = app::App::new(
+		u32::from(w),
+		u32::from(h),
+		VIEW_SCALE_BASE,
+		config_home,
+		&res,
+		minion_gene_pool,
+		world_file,
+		seed,
+		run_options.config.clone(),
+	)
+	.unwrap_or_else(|e| exit_with_error(&e));
+	app.set_options(run_options);
 
 	let mut ui = ui::conrod_ui::Ui::new(&res, &mut factory, &frame_buffer, f64::from(window.hidpi_factor()))
 		.expect("Unable to create UI");
 
-	let audio = audio::ThreadedSoundSystem::new(audio_device).expect("Failure in audio initialization");
+	#[cfg(feature = "audio")]
+	let mut audio_alert_player = {
+		let audio = audio::ThreadedSoundSystem::new(audio_device).expect("Failure in audio initialization");
+		audio::ThreadedAlertPlayer::new(audio)
+	};
+	#[cfg(not(feature = "audio"))]
+	let mut audio_alert_player = {
+		if audio_device.is_some() {
+			warn!("Audio support not available in this build");
+		}
+		ui::NullAlertPlayer::new()
+	};
 	let mut no_audio = ui::NullAlertPlayer::new();
-	let mut audio_alert_player = audio::ThreadedAlertPlayer::new(audio);
 	app.init(app::SystemMode::Interactive);
 
 	'main: loop {
-		maybe_gamepad = maybe_gamepad.map(|mut gamepad| {
-			gamepad.poll_events(|event| app.on_input_event(&event));
-			gamepad
-		});
+		#[cfg(feature = "gamepad")]
+		{
+			maybe_gamepad = maybe_gamepad.map(|mut gamepad| {
+				gamepad.poll_events(|event| app.on_input_event(&event));
+				gamepad
+			});
+		}
 
 		events_loop.poll_events(|event| {
 			if app.has_ui_overlay() {
@@ -173,12 +224,31 @@ pub fn main_loop(
 	}
 }
 
-pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, world_file: Option<path::PathBuf>) {
+pub fn main_loop_headless(
+	minion_gene_pool: &str,
+	config_home: path::PathBuf,
+	world_file: Option<path::PathBuf>,
+	seed: Option<Seed>,
+	run_options: RunOptions,
+	limits: &RunLimits,
+) -> StopReason {
 	const WIDTH: u32 = 1024;
 	const HEIGHT: u32 = 1024;
 	let res = make_resource_loader(&config_home);
 
-	let mut app = app::App::new(WIDTH, HEIGHT, VIEW_SCALE_BASE, config_home, &res, minion_gene_pool, world_file);
+	let mut app = app::App::new(
+		WIDTH,
+		HEIGHT,
+		VIEW_SCALE_BASE,
+		config_home,
+		&res,
+		minion_gene_pool,
+		world_file,
+		seed,
+		run_options.config.clone(),
+	)
+	.unwrap_or_else(|e| exit_with_error(&e));
+	app.set_options(run_options);
 	let mut no_audio = ui::NullAlertPlayer::new();
 	app.init(app::SystemMode::Batch);
 
@@ -193,20 +263,45 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 	let wall_clock = SystemTimer::new();
 	let mut output_hourglass = Hourglass::new(seconds(LOG_INTERVAL), &wall_clock);
 	let mut save_hourglass = Hourglass::new(seconds(SAVE_INTERVAL), &wall_clock);
+	let initial_extinctions = app.extinctions();
+	let mut progress = RunProgress::default();
 
 	const FRAME_SIMULATION_LENGTH: SecondsValue = FRAME_TIME_TARGET;
-	'main: loop {
+	let stop_reason = 'main: loop {
 		if !app.is_running() {
-			break 'main;
+			break 'main StopReason::Quit;
+		}
+
+		if app.is_replay_finished() {
+			info!("Stopping: {} after {} steps", StopReason::EndOfReplay, progress.steps);
+			app.save_world_to_file();
+			break 'main StopReason::EndOfReplay;
 		}
 
 		if !running.load(Ordering::SeqCst) {
 			eprintln!("Interrupted, exiting");
 			app.save_world_to_file();
-			break 'main;
+			break 'main StopReason::Interrupted;
 		}
 		// update and measure
 		let simulation_update = app.simulate(seconds(FRAME_SIMULATION_LENGTH));
+		progress.update(&simulation_update, initial_extinctions);
+		#[cfg(feature = "graphics")]
+		app.record_timelapse(progress.steps);
+		progress.wall_clock_time = wall_clock.seconds();
+		if let Some(reason) = limits.check(&progress) {
+			info!(
+				"Stopping: {} after {} steps, {:.3} simulated, {:.3} wall clock, P: {} X: {}",
+				reason,
+				progress.steps,
+				progress.simulation_time,
+				progress.wall_clock_time,
+				progress.population,
+				progress.total_extinctions
+			);
+			app.save_world_to_file();
+			break 'main reason;
+		}
 		if save_hourglass.flip_if_expired(&wall_clock) {
 			app.save_world_to_file();
 		}
@@ -214,13 +309,17 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 		app.play_alerts(&mut no_audio);
 		if output_hourglass.flip_if_expired(&wall_clock) {
 			info!(
-				"C: {} E: {:.3} FT: {:.2} P: {} X: {}",
+				"C: {} E: {:.3} FT: {:.2} P: {} X: {} S: {}",
 				simulation_update.count,
 				simulation_update.elapsed,
 				simulation_update.dt,
 				simulation_update.population,
-				simulation_update.extinctions
+				simulation_update.extinctions,
+				simulation_update.conditions
 			)
 		}
-	}
+	};
+	#[cfg(feature = "graphics")]
+	app.save_final_frame();
+	stop_reason
 }
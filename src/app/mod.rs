@@ -1,12 +1,24 @@
 use app::constants::*;
+use backend::config::{ConfigSource, Scope, SimulationConfig};
 use backend::messagebus::{Inbox, Message, Outbox, PubSub, ReceiveDrain, Whiteboard};
-use backend::obj;
 use backend::obj::*;
-use backend::systems;
+use backend::stats::StatsRecorder;
+use backend::svg;
+pub use backend::systems::SystemMode;
+use backend::systems::Systems;
 use backend::world;
 use backend::world::agent;
+use backend::world::environment::Conditions;
+use backend::world::gen;
+use backend::world::persist;
+use backend::world::persist::SnapshotFormat;
+use backend::world::scenario::Scenario;
 use backend::world::segment;
+use backend::world::selection;
+use backend::world::selection::Selection;
+#[cfg(feature = "graphics")]
 use cgmath;
+#[cfg(feature = "graphics")]
 use cgmath::Matrix4;
 use core::clock::*;
 use core::geometry::Transform;
@@ -16,45 +28,68 @@ use core::math::Directional;
 use core::math::Relative;
 use core::math::Smooth;
 use core::resource::ResourceLoader;
+use core::rng;
 use core::util::Cycle;
 use core::view::Viewport;
 use core::view::WorldTransform;
 use dirs;
 use frontend::input;
+#[cfg(feature = "graphics")]
 use frontend::render;
 use frontend::ui;
+use getopts;
 use getopts::Options;
 use num;
-use rayon::prelude::*;
-use std::ffi::OsStr;
+use serialize::base64::FromBase64;
 use std::fs;
+use std::io;
 use std::path;
 
 pub use self::controller::DefaultController;
 pub use self::controller::InputController;
 pub use self::events::Event;
 use self::events::VectorDirection;
+use self::journal::{JournalWriter, Replay};
+pub use self::limits::{RunLimits, RunProgress, StopReason};
+#[cfg(feature = "graphics")]
+use self::timelapse::{Framing, Timelapse};
+#[cfg(feature = "graphics")]
 pub use self::winit_event::WinitEventMapper;
+#[cfg(feature = "graphics")]
 pub use self::winit_event::WinitEventMapper as EventMapper;
 use std::ffi::OsString;
 use std::fmt::Debug;
 use std::iter::Iterator;
 use std::process;
-use std::sync::Arc;
-use std::sync::RwLock;
+use std::str;
 
 //#[cfg(feature="capture")]
+#[cfg(feature = "graphics")]
 mod capture;
 
 mod controller;
 mod events;
+pub mod journal;
+mod limits;
 mod main;
+#[cfg(feature = "graphics")]
 mod paint;
+#[cfg(feature = "graphics")]
+pub mod timelapse;
+#[cfg(feature = "graphics")]
 mod winit_event;
 
 pub mod constants;
 
 pub fn run(args: &[OsString]) {
+	match args.get(1).and_then(|a| a.to_str()) {
+		Some("convert") => process::exit(convert(&args[2..])),
+		Some("inspect") => process::exit(inspect(&args[2..])),
+		Some("decode") => process::exit(decode(&args[2..])),
+		Some("svg") => process::exit(svg(&args[2..])),
+		Some("merge") => process::exit(merge(&args[2..])),
+		_ => {}
+	}
 	let mut opt = Options::new();
 	opt.optflag("t", "terminal", "Headless mode");
 	opt.optopt("f", "fullscreen", "Fullscreen mode on monitor X", "0");
@@ -63,6 +98,44 @@ pub fn run(args: &[OsString]) {
 	opt.optopt("w", "width", "Window width", "1024");
 	opt.optopt("h", "height", "Window height", "1024");
 	opt.optopt("a", "audio_device", "Audio device index (portaudio)", "0");
+	opt.optopt("s", "seed", "Random seed, for reproducible runs", "12345");
+	opt.optopt("", "steps", "Headless mode: stop after N simulation steps", "100000");
+	opt.optopt("", "sim-time", "Headless mode: stop after N seconds of simulated time", "3600");
+	opt.optopt("", "wall-time", "Headless mode: stop after N seconds of wall clock time", "3600");
+	opt.optflag("", "stop-on-extinction", "Headless mode: stop at the first extinction");
+	opt.optopt("", "min-population", "Headless mode: stop when the population drops below N", "10");
+	opt.optopt("", "max-population", "Headless mode: stop when the population rises above N", "1000");
+	opt.optopt("", "max-extinctions", "Headless mode: stop when the extinction count reaches N", "5");
+	opt.optopt("", "stats", "Record population statistics to a CSV or JSON Lines (.jsonl) file", "stats.csv");
+	opt.optopt("", "stats-interval", "Statistics sampling interval, in seconds of simulated time", "10");
+	opt.optmulti(
+		"",
+		"family-tree",
+		"Save the family tree with each snapshot, as Newick or GraphML (.graphml)",
+		"tree.nwk",
+	);
+	opt.optopt("", "record", "Record the session to a journal file, for replay", "session.jsonl");
+	opt.optopt("", "replay", "Replay a session from a journal file", "session.jsonl");
+	opt.optopt("", "species-threshold", "Maximum genome distance in bits between members of a species", "48");
+	// the software renderer draws through the render traits of the front end
+	#[cfg(feature = "graphics")]
+	{
+		opt.optopt("", "png", "Headless mode: render the last frame to a PNG file, with no GPU", "final.png");
+		opt.optopt(
+			"",
+			"timelapse",
+			"Headless mode: save a frame every few steps to a directory, with no GPU",
+			"timelapse",
+		);
+		opt.optopt("", "timelapse-interval", "Headless mode: simulation steps between timelapse frames", "600");
+		opt.optopt("", "timelapse-size", "Headless mode: size of the timelapse frames", "1024x1024");
+		opt.optopt("", "timelapse-camera", "Headless mode: timelapse framing, extent or x,y,width", "extent");
+	}
+	opt.optopt("", "config", "Load the simulation parameters from a TOML or JSON file", "rust-oids.toml");
+	opt.optmulti("", "set", "Override a simulation parameter", "world_radius=100");
+	opt.optopt("", "scenario", "Start a new world laid out by a TOML or JSON scenario file", "arena.toml");
+	opt.optopt("", "selection", "Gene pool selection: round-robin, random, fitness or tournament:N", "round-robin");
+	opt.optopt("", "snapshot-format", "Format of the saved snapshots: json, cbor or cbor.gz", "json");
 	match opt.parse(args) {
 		Ok(options) => {
 			let pool_file_name = options.free.get(1).map(String::as_str).unwrap_or(DEFAULT_MINION_GENE_POOL_FILE);
@@ -71,16 +144,28 @@ pub fn run(args: &[OsString]) {
 
 			// we look for the last save in ~/.config/rust-oids/saved_state
 			// but only if -n and -i are not specified
-			let user_home = dirs::home_dir().unwrap_or_else(|| path::PathBuf::from("."));
-			let config_home = user_home.join(CONFIG_DIR_HOME);
-			if !options.opt_present("n") && world_file.is_none() {
+			let config_home = config_home();
+			let replay = options.opt_str("replay").map(|file_name| {
+				Replay::open(path::Path::new(&file_name)).unwrap_or_else(|e| {
+					eprintln!("Unable to replay {}: {}", file_name, e);
+					process::exit(1)
+				})
+			});
+			let scenario = options.opt_str("scenario").map(|file_name| {
+				Scenario::load(path::Path::new(&file_name)).unwrap_or_else(|e| {
+					eprintln!("Unable to load {}: {}", file_name, e);
+					process::exit(1)
+				})
+			});
+			// a scenario always starts from a new world
+			if !options.opt_present("n") && world_file.is_none() && replay.is_none() && scenario.is_none() {
 				let mut max_path = None;
 				if let Ok(dir) = fs::read_dir(config_home.join(CONFIG_DIR_SAVED_STATE)) {
 					// get the highest file in lexicographical order
 					// or the first one, if any
 					for entry in dir {
 						let path_name = entry.unwrap().path().to_owned();
-						if path_name.extension().to_owned().and_then(OsStr::to_str) == Some("json")
+						if SnapshotFormat::is_snapshot(&path_name)
 							&& max_path.as_ref().map(|m| path_name > *m).unwrap_or(true)
 						{
 							max_path = Some(path_name.clone());
@@ -93,15 +178,113 @@ pub fn run(args: &[OsString]) {
 				}
 			}
 
-			if options.opt_present("t") {
-				main::main_loop_headless(pool_file_name, config_home, world_file);
-			} else {
+			// a replay starts from the snapshot stored in its journal
+			let seed = match replay {
+				Some(ref replay) => Some(replay.seed()),
+				None => parse_opt::<rng::Seed>(&options, "s"),
+			};
+			let stats_interval = parse_opt::<SecondsValue>(&options, "stats-interval").unwrap_or(STATS_INTERVAL);
+			if stats_interval.is_nan() || stats_interval <= 0. {
+				eprintln!("Invalid value for --stats-interval: {}, it must be positive", stats_interval);
+				process::exit(1)
+			}
+			let mut config_source = config_source(&options);
+			let run_options = RunOptions {
+				stats: options.opt_str("stats").map(|file_name| {
+					StatsRecorder::create(path::Path::new(&file_name), seconds(stats_interval)).unwrap_or_else(|e| {
+						eprintln!("Unable to create {}: {}", file_name, e);
+						process::exit(1)
+					})
+				}),
+				family_tree: options.opt_strs("family-tree").iter().map(path::PathBuf::from).collect(),
+				species_threshold: parse_opt::<usize>(&options, "species-threshold"),
+				journal: options.opt_str("record").map(|file_name| {
+					JournalWriter::create(path::Path::new(&file_name)).unwrap_or_else(|e| {
+						eprintln!("Unable to create {}: {}", file_name, e);
+						process::exit(1)
+					})
+				}),
+				replay,
+				#[cfg(feature = "graphics")]
+				final_frame: options.opt_str("png").map(path::PathBuf::from),
+				#[cfg(feature = "graphics")]
+				timelapse: options.opt_str("timelapse").map(|dir| {
+					let interval = parse_opt(&options, "timelapse-interval").unwrap_or(TIMELAPSE_INTERVAL);
+					let (width, height) = match options.opt_str("timelapse-size") {
+						None => (TIMELAPSE_SIZE, TIMELAPSE_SIZE),
+						Some(size) => parse_size(&size).unwrap_or_else(|| {
+							eprintln!("Invalid timelapse size: {}", size);
+							process::exit(1)
+						}),
+					};
+					let framing = match options.opt_str("timelapse-camera") {
+						None => Framing::Extent,
+						Some(camera) => Framing::parse(&camera).unwrap_or_else(|| {
+							eprintln!("Invalid timelapse camera: {}", camera);
+							process::exit(1)
+						}),
+					};
+					Timelapse::create(path::Path::new(&dir), interval, width, height, framing).unwrap_or_else(|e| {
+						eprintln!("Unable to create {}: {}", dir, e);
+						process::exit(1)
+					})
+				}),
+				config: config_source.as_mut().map(|source| {
+					source.load().unwrap_or_else(|e| {
+						eprintln!("{}", e);
+						process::exit(1)
+					})
+				}),
+				config_source,
+				scenario,
+				selection: options.opt_str("selection").map(|policy| {
+					selection::parse(&policy).unwrap_or_else(|| {
+						eprintln!("Unknown selection policy: {}", policy);
+						process::exit(1)
+					})
+				}),
+				snapshot_format: match options.opt_str("snapshot-format") {
+					None => SnapshotFormat::default(),
+					Some(extension) => SnapshotFormat::from_extension(&extension).unwrap_or_else(|| {
+						eprintln!("Unknown snapshot format: {}", extension);
+						process::exit(1)
+					}),
+				},
+			};
+
+			// without graphics, headless is the only mode available
+			if options.opt_present("t") || cfg!(not(feature = "graphics")) {
+				let limits = RunLimits {
+					steps: parse_opt(&options, "steps"),
+					simulation_time: parse_opt(&options, "sim-time"),
+					wall_clock_time: parse_opt(&options, "wall-time"),
+					stop_on_extinction: options.opt_present("stop-on-extinction"),
+					min_population: parse_opt(&options, "min-population"),
+					max_population: parse_opt(&options, "max-population"),
+					max_extinctions: parse_opt(&options, "max-extinctions"),
+				};
+				let stop_reason =
+					main::main_loop_headless(pool_file_name, config_home, world_file, seed, run_options, &limits);
+				process::exit(stop_reason.exit_code());
+			}
+			#[cfg(feature = "graphics")]
+			{
 				let fullscreen = options.opt_default("f", "0").and_then(|v| v.parse::<usize>().ok());
 				let width = options.opt_default("w", "1024").and_then(|v| v.parse::<u32>().ok());
 				let height = options.opt_default("h", "1024").and_then(|v| v.parse::<u32>().ok());
 				let audio_device = options.opt_default("a", "0").and_then(|v| v.parse::<usize>().ok());
 
-				main::main_loop(pool_file_name, config_home, world_file, fullscreen, width, height, audio_device);
+				main::main_loop(
+					pool_file_name,
+					config_home,
+					world_file,
+					seed,
+					run_options,
+					fullscreen,
+					width,
+					height,
+					audio_device,
+				);
 			}
 		}
 		Err(message) => {
@@ -112,130 +295,225 @@ pub fn run(args: &[OsString]) {
 	}
 }
 
-#[derive(Default)]
-struct SendSystem<T>
-where T: systems::System {
-	ptr: Arc<RwLock<T>>,
-}
-
-impl<T> SendSystem<T>
-where T: systems::System
-{
-	fn boxed(ptr: Arc<RwLock<T>>) -> Box<Self> { Box::new(SendSystem { ptr }) }
+/// The value of an option, if given. Exits if it is not valid
+fn parse_opt<T>(options: &getopts::Matches, name: &str) -> Option<T>
+where T: str::FromStr {
+	options.opt_str(name).map(|value| {
+		value.parse::<T>().unwrap_or_else(|_| {
+			eprintln!("Invalid value for {}{}: {}", if name.len() > 1 { "--" } else { "-" }, name, value);
+			process::exit(1)
+		})
+	})
 }
 
-impl<T> systems::System for SendSystem<T>
-where T: systems::System
-{
-	fn attach(&mut self, bus: &mut PubSub) { self.ptr.write().unwrap().attach(bus) }
-	fn init(&mut self, world: &world::World) { self.ptr.write().unwrap().init(world) }
-	fn clear(&mut self) { self.ptr.write().unwrap().clear() }
-	fn register(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().register(agent) }
-	fn unregister(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().unregister(agent) }
-
-	fn step(&mut self, world: &world::World, dt: Seconds) { self.ptr.write().unwrap().step(world, dt) }
-	fn apply(&self, world: &mut world::World, outbox: &dyn Outbox) { self.ptr.read().unwrap().apply(world, outbox) }
+/// `WIDTHxHEIGHT`
+#[cfg(feature = "graphics")]
+fn parse_size(s: &str) -> Option<(u32, u32)> {
+	let mut parts = s.splitn(2, 'x');
+	let width = parts.next().and_then(|v| v.parse::<u32>().ok()).filter(|w| *w > 0)?;
+	let height = parts.next().and_then(|v| v.parse::<u32>().ok()).filter(|h| *h > 0)?;
+	Some((width, height))
 }
 
-// unsafe?
-unsafe impl<T> Send for SendSystem<T> where T: systems::System {}
-
-#[derive(Copy, Clone)]
-pub enum SystemMode {
-	Interactive,
-	Batch,
+fn config_home() -> path::PathBuf {
+	let user_home = dirs::home_dir().unwrap_or_else(|| path::PathBuf::from("."));
+	user_home.join(CONFIG_DIR_HOME)
 }
 
-impl Default for SystemMode {
-	fn default() -> Self { SystemMode::Interactive }
+/// `rust-oids convert <input> <output>` rewrites a snapshot in the format
+/// given by the extension of the output file
+fn convert(args: &[OsString]) -> i32 {
+	match args {
+		[input, output] => match persist::Serializer::convert(path::Path::new(input), path::Path::new(output)) {
+			Ok(()) => 0,
+			Err(e) => {
+				eprintln!("Unable to convert {:?} to {:?}: {}", input, output, e);
+				1
+			}
+		},
+		_ => {
+			eprintln!("Usage: rust-oids convert <input> <output>");
+			1
+		}
+	}
 }
 
-#[derive(Default)]
-pub struct Systems {
-	mode: SystemMode,
-	physics: Arc<RwLock<systems::PhysicsSystem>>,
-	animation: Arc<RwLock<systems::AnimationSystem>>,
-	game: Arc<RwLock<systems::GameSystem>>,
-	ai: Arc<RwLock<systems::AiSystem>>,
-	alife: Arc<RwLock<systems::AlifeSystem>>,
-	particle: Arc<RwLock<systems::ParticleSystem>>,
+/// `rust-oids inspect <snapshot>` prints a report on a snapshot, as text or
+/// JSON
+fn inspect(args: &[OsString]) -> i32 {
+	let mut opt = Options::new();
+	opt.optflag("", "json", "Print the report as JSON");
+	opt.optopt("", "top", "Number of genomes, species and agents listed", "10");
+	let options = match opt.parse(args) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("Invalid option: {:?}", message);
+			return 1;
+		}
+	};
+	if options.free.len() != 1 {
+		eprintln!("{}", opt.usage("Usage: rust-oids inspect [Options] <snapshot>"));
+		return 1;
+	}
+	let snapshot_file = &options.free[0];
+	let top = parse_opt::<usize>(&options, "top").unwrap_or(INSPECT_TOP);
+	let result = persist::Serializer::read(path::Path::new(snapshot_file)).and_then(|snapshot| {
+		let report = snapshot.report(top);
+		let stdout = io::stdout();
+		let mut out = stdout.lock();
+		if options.opt_present("json") {
+			report.write_json(&mut out)
+		} else {
+			report.write_text(&mut out)
+		}
+	});
+	match result {
+		Ok(()) => 0,
+		Err(e) => {
+			eprintln!("Unable to inspect {}: {}", snapshot_file, e);
+			1
+		}
+	}
 }
 
-impl Systems {
-	fn set_mode(&mut self, mode: SystemMode) { self.mode = mode; }
-
-	fn systems(&mut self) -> Vec<Box<dyn systems::System + Send>> {
-		match self.mode {
-			SystemMode::Interactive => vec![
-				SendSystem::boxed(self.physics.clone()),
-				SendSystem::boxed(self.animation.clone()),
-				SendSystem::boxed(self.particle.clone()),
-				SendSystem::boxed(self.game.clone()),
-				SendSystem::boxed(self.ai.clone()),
-				SendSystem::boxed(self.alife.clone()),
-			],
-			SystemMode::Batch => vec![
-				SendSystem::boxed(self.physics.clone()),
-				SendSystem::boxed(self.game.clone()),
-				SendSystem::boxed(self.ai.clone()),
-				SendSystem::boxed(self.alife.clone()),
-			],
-		}
-	}
-
-	pub fn unregister(&mut self, agents: &[world::agent::Agent]) {
-		if !agents.is_empty() {
-			self.systems().par_iter_mut().for_each(|system| {
-				for agent in agents {
-					system.unregister(agent)
-				}
-			})
+/// `rust-oids decode <dna>` explains a base64 minion genome
+fn decode(args: &[OsString]) -> i32 {
+	let mut opt = Options::new();
+	opt.optflag("", "json", "Print the phenotype as JSON");
+	let options = match opt.parse(args) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("Invalid option: {:?}", message);
+			return 1;
 		}
+	};
+	if options.free.len() != 1 {
+		eprintln!("{}", opt.usage("Usage: rust-oids decode [Options] <dna>"));
+		return 1;
 	}
-
-	pub fn register(&mut self, agents: &[world::agent::Agent]) {
-		if !agents.is_empty() {
-			self.systems().par_iter_mut().for_each(|system| {
-				for agent in agents {
-					system.register(&agent)
+	let dna = &options.free[0];
+	match world::decode::decode_minion_base64(dna) {
+		Ok(phenotype) => {
+			let stdout = io::stdout();
+			let mut out = stdout.lock();
+			let result = if options.opt_present("json") {
+				phenotype.write_json(&mut out)
+			} else {
+				phenotype.write_text(&mut out)
+			};
+			match result {
+				Ok(()) => 0,
+				Err(e) => {
+					eprintln!("Unable to write the phenotype: {}", e);
+					1
 				}
-			})
+			}
 		}
-	}
-
-	fn init(&mut self, world: &world::World) {
-		for system in &mut self.systems() {
-			system.init(world);
+		Err(e) => {
+			eprintln!("Invalid DNA {}: {}", dna, e);
+			1
 		}
 	}
+}
 
-	fn clear(&mut self) {
-		for system in &mut self.systems() {
-			system.clear();
+/// `rust-oids svg <snapshot> <output>` draws a whole world, `rust-oids svg
+/// --dna <dna> <output>` or `--gene-pool <file>` a specimen sheet
+fn svg(args: &[OsString]) -> i32 {
+	let mut opt = Options::new();
+	opt.optmulti("", "dna", "Draw a specimen from a base64 genome, can be given more than once", "GyA21QoQ");
+	opt.optopt("", "gene-pool", "Draw a specimen for each genome in a gene pool file", "minion_gene_pool.csv");
+	let options = match opt.parse(args) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("Invalid option: {:?}", message);
+			return 1;
+		}
+	};
+	let is_specimen_sheet = options.opt_present("dna") || options.opt_present("gene-pool");
+	let result = match (is_specimen_sheet, options.free.as_slice()) {
+		(true, [output]) => specimen_dnas(&options)
+			.and_then(|dnas| svg::save_specimen_sheet(path::Path::new(output), &svg::specimens(&dnas))),
+		(false, [snapshot, output]) => {
+			let res = main::make_resource_loader(&config_home());
+			let mut world = world::World::new(&res, DEFAULT_MINION_GENE_POOL_FILE, 0);
+			persist::Serializer::load(path::Path::new(snapshot), &mut world)
+				.and_then(|_| svg::save_world(path::Path::new(output), &world))
+		}
+		_ => {
+			eprintln!("{}", opt.usage("Usage: rust-oids svg [Options] [<snapshot>] <output.svg>"));
+			return 1;
+		}
+	};
+	match result {
+		Ok(()) => 0,
+		Err(e) => {
+			eprintln!("Unable to export SVG: {}", e);
+			1
 		}
 	}
+}
 
-	fn attach(&mut self, bus: &mut PubSub) {
-		for system in &mut self.systems() {
-			system.attach(bus);
+fn specimen_dnas(options: &getopts::Matches) -> io::Result<Vec<gen::Dna>> {
+	let mut dnas = Vec::new();
+	for dna in options.opt_strs("dna") {
+		match dna.from_base64() {
+			Ok(dna) => dnas.push(dna.into_boxed_slice()),
+			Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid DNA {}: {}", dna, e))),
 		}
 	}
+	if let Some(file_name) = options.opt_str("gene-pool") {
+		let gene_pool = gen::GenePool::parse_from_resource(&fs::read(&file_name)?)
+			.map_err(|e| io::Error::new(e.kind(), format!("{}, {}", file_name, e)))?;
+		dnas.extend(gene_pool.gene_pool_iter().cloned());
+	}
+	Ok(dnas)
+}
 
-	fn for_each_read(
-		&mut self,
-		world: &mut world::World,
-		outbox: &dyn Outbox,
-		apply: &(dyn Fn(&mut dyn systems::System, &mut world::World, &dyn Outbox) + Sync),
-	) {
-		self.systems().iter_mut().for_each(|r| apply(&mut (**r), world, outbox))
+/// The file given with `--config` and the overrides given with `--set`. None
+/// if neither is given
+fn config_source(options: &getopts::Matches) -> Option<ConfigSource> {
+	let file_path = options.opt_str("config").map(path::PathBuf::from);
+	let overrides = options.opt_strs("set");
+	if file_path.is_none() && overrides.is_empty() {
+		None
+	} else {
+		Some(ConfigSource::new(file_path, overrides))
 	}
+}
 
-	fn for_each_par_write(
-		&mut self,
-		world: &world::World,
-		apply: &(dyn Fn(&mut dyn systems::System, &world::World) + Sync),
-	) {
-		self.systems().par_iter_mut().for_each(|r| apply(&mut (**r), world))
+/// `rust-oids merge <output> <input>...` writes the genomes of several gene
+/// pool files into one, with their metadata
+fn merge(args: &[OsString]) -> i32 {
+	match args.split_first() {
+		Some((output, inputs)) if !inputs.is_empty() => {
+			let result = inputs
+				.iter()
+				.map(|input| {
+					fs::read(input)
+						.and_then(|data| gen::GenePool::parse_from_resource(&data))
+						.map_err(|e| io::Error::new(e.kind(), format!("{}, {}", input.to_string_lossy(), e)))
+				})
+				.collect::<io::Result<Vec<_>>>()
+				.and_then(|pools| {
+					let merged = gen::GenePool::merge(&pools);
+					merged.write(io::BufWriter::new(fs::File::create(output)?))?;
+					Ok(merged.len())
+				});
+			match result {
+				Ok(count) => {
+					println!("Saved {} genomes to {:?}", count, output);
+					0
+				}
+				Err(e) => {
+					eprintln!("Unable to merge into {:?}: {}", output, e);
+					1
+				}
+			}
+		}
+		_ => {
+			eprintln!("Usage: rust-oids merge <output.csv> <input.csv>...");
+			1
+		}
 	}
 }
 
@@ -260,6 +538,7 @@ pub struct App {
 	is_running: bool,
 	is_paused: bool,
 	is_capturing: bool,
+	is_species_tint: bool,
 	// interactions: Vec<Event>,
 	camera: math::Inertial<f32>,
 	is_camera_tracking: bool,
@@ -272,6 +551,20 @@ pub struct App {
 	reply_inbox: Inbox,
 	alert_inbox: Inbox,
 	systems: Systems,
+	stats: Option<StatsRecorder>,
+	family_tree_files: Vec<path::PathBuf>,
+	journal: Option<JournalWriter>,
+	replay: Option<Replay>,
+	#[cfg(feature = "graphics")]
+	final_frame: Option<path::PathBuf>,
+	#[cfg(feature = "graphics")]
+	timelapse: Option<Timelapse>,
+	snapshot_format: SnapshotFormat,
+	config_source: Option<ConfigSource>,
+	config_reload: bool,
+	config_checked: SecondsValue,
+	/// Index of the feeder being moved with the mouse
+	dragged_feeder: Option<usize>,
 	//
 	#[allow(unused)]
 	config_home: path::PathBuf,
@@ -282,6 +575,37 @@ pub struct App {
 	has_ui_overlay: bool,
 }
 
+/// Optional inputs and outputs of a run, in either mode
+#[derive(Default)]
+pub struct RunOptions {
+	pub stats: Option<StatsRecorder>,
+	/// Saved along with each snapshot, as GraphML or Newick depending on the
+	/// extension
+	pub family_tree: Vec<path::PathBuf>,
+	/// Overrides the default distance between members of the same species
+	pub species_threshold: Option<usize>,
+	/// Records the events sent to the app and the length of each step
+	pub journal: Option<JournalWriter>,
+	/// Replaces the user input with a recorded session
+	pub replay: Option<Replay>,
+	/// Rendered in software at the end of a headless run
+	#[cfg(feature = "graphics")]
+	pub final_frame: Option<path::PathBuf>,
+	/// Rendered in software during a headless run
+	#[cfg(feature = "graphics")]
+	pub timelapse: Option<Timelapse>,
+	/// Lays out the world, unless it is restored from a snapshot
+	pub scenario: Option<Scenario>,
+	/// How the minion gene pool seeds new minions
+	pub selection: Option<Box<dyn Selection>>,
+	/// Replaces the defaults, or the configuration stored in the snapshot
+	pub config: Option<SimulationConfig>,
+	/// Read again when the file changes, or on Event::Reload
+	pub config_source: Option<ConfigSource>,
+	pub snapshot_format: SnapshotFormat,
+}
+
+#[cfg(feature = "graphics")]
 pub struct Environment {
 	pub lights: Box<[render::Light]>,
 	pub background_color: Rgba,
@@ -295,6 +619,9 @@ pub struct SimulationUpdate {
 	pub elapsed: Seconds,
 	pub population: usize,
 	pub extinctions: usize,
+	/// Species sizes, largest first
+	pub species: Vec<usize>,
+	pub conditions: Conditions,
 }
 
 #[derive(Clone, Debug)]
@@ -310,6 +637,7 @@ pub struct FrameUpdate {
 }
 
 impl App {
+	#[allow(clippy::too_many_arguments)]
 	pub fn new<R>(
 		w: u32,
 		h: u32,
@@ -318,7 +646,9 @@ impl App {
 		resource_loader: &R,
 		minion_gene_pool: &str,
 		world_file: Option<path::PathBuf>,
-	) -> Self
+		seed: Option<rng::Seed>,
+		config: Option<SimulationConfig>,
+	) -> io::Result<Self>
 	where
 		R: ResourceLoader<u8>,
 	{
@@ -327,15 +657,29 @@ impl App {
 		let alert_inbox = bus.subscribe(Box::new(|e| matches!(*e, Message::Alert(_) | Message::Event(_))));
 		let reply_inbox = bus.subscribe(Box::new(|e| matches!(*e, Message::Event(Event::SelectMinion(_)))));
 
-		let mut new_world = world::World::new(resource_loader, minion_gene_pool);
-		let last_saved = world_file.map(|world_file| {
-			if world::persist::Serializer::load(&world_file, &mut new_world).is_err() {
-				panic!("Could not load {:?}", &world_file);
-			}
-			world_file
-		});
+		let mut new_world = world::World::with_config(
+			resource_loader,
+			minion_gene_pool,
+			seed.unwrap_or_else(rng::random_seed),
+			config.clone().unwrap_or_default(),
+		);
+		if let Some(ref world_file) = world_file {
+			world::persist::Serializer::load(world_file, &mut new_world)
+				.map_err(|e| io::Error::new(e.kind(), format!("Could not load {:?}: {}", world_file, e)))?;
+		}
+		// an explicit configuration takes precedence over the one stored in the
+		// snapshot, except for the layout of the world
+		if let Some(config) = config {
+			new_world.set_config(config);
+		}
+		let last_saved = world_file;
+		// an explicit seed takes precedence over the one stored in the snapshot
+		if let Some(seed) = seed {
+			new_world.reseed(seed);
+		}
+		info!("Random seed: {}", new_world.seed());
 
-		App {
+		Ok(App {
 			viewport: Viewport::rect(w, h, scale),
 			zoom: math::exponential_filter(1., 1., VIEW_ZOOM_DURATION),
 			input_state: input::InputState::default(),
@@ -352,6 +696,19 @@ impl App {
 			reply_inbox,
 			// subsystems
 			systems: Systems::default(),
+			stats: None,
+			family_tree_files: Vec::new(),
+			journal: None,
+			replay: None,
+			#[cfg(feature = "graphics")]
+			final_frame: None,
+			#[cfg(feature = "graphics")]
+			timelapse: None,
+			snapshot_format: SnapshotFormat::default(),
+			config_source: None,
+			config_reload: false,
+			config_checked: 0.,
+			dragged_feeder: None,
 			// runtime and timing
 			simulations_count: 0usize,
 			frame_count: 0usize,
@@ -362,6 +719,7 @@ impl App {
 			is_running: true,
 			is_paused: false,
 			is_capturing: false,
+			is_species_tint: false,
 			// savegame
 			saved_state_dir: config_home.join(CONFIG_DIR_SAVED_STATE),
 			config_home,
@@ -369,7 +727,7 @@ impl App {
 			// debug
 			debug_flags: DebugFlags::empty(),
 			has_ui_overlay: true,
-		}
+		})
 	}
 
 	fn on_app_event(&mut self, e: Event) {
@@ -427,6 +785,7 @@ impl App {
 				self.speed_factors.prev();
 			}
 			Event::ToggleDebug => self.debug_flags.toggle(DebugFlags::DEBUG_TARGETS),
+			Event::ToggleSpeciesTint => self.is_species_tint = !self.is_species_tint,
 			Event::RestartFromCheckpoint => self.restart_from_checkpoint(),
 
 			Event::AppQuit => self.quit(),
@@ -451,8 +810,18 @@ impl App {
 			Event::NewMinion(pos) => self.new_minion(pos),
 			Event::RandomizeMinion(pos) => self.randomize_minion(pos),
 			Event::PrimaryFire(_, _) => { /* Handled by the gameplay system */ }
-			Event::Reload => { /* Handled in the main loop */ }
+			Event::Reload => self.config_reload = true,
 			Event::PickMinion(_) => { /* Handled by the physics system */ }
+			Event::AddFeeder(pos) => self.add_feeder(pos),
+			Event::RemoveFeeder(pos) => self.remove_feeder(pos),
+			Event::BeginFeederDrag(pos) => self.dragged_feeder = self.world.feeder_at(pos),
+			Event::FeederDrag(pos) => self.drag_feeder(pos),
+			Event::EndFeederDrag(pos) => {
+				self.drag_feeder(pos);
+				self.dragged_feeder = None;
+			}
+			Event::ScaleFeederRate(pos, factor) => self.scale_feeder_rate(pos, factor),
+			Event::ScaleFeederPower(pos, factor) => self.scale_feeder_power(pos, factor),
 		}
 	}
 
@@ -468,6 +837,43 @@ impl App {
 
 	fn new_minion(&mut self, pos: Position) { self.world.new_minion(pos, Motion::default()); }
 
+	fn add_feeder(&mut self, pos: Position) {
+		let index = self.world.add_feeder(pos);
+		info!("Added feeder {} at ({:.1}, {:.1})", index, pos.x, pos.y);
+	}
+
+	fn remove_feeder(&mut self, pos: Position) {
+		if let Some(index) = self.world.feeder_at(pos) {
+			self.world.remove_feeder(index);
+			self.dragged_feeder = None;
+			info!("Removed feeder {}", index);
+		}
+	}
+
+	fn drag_feeder(&mut self, pos: Position) {
+		if let Some(feeder) = self.dragged_feeder.and_then(|index| self.world.feeders_mut().get_mut(index)) {
+			let angle = feeder.transform().angle;
+			feeder.transform_to(Transform::new(pos, angle));
+		}
+	}
+
+	fn scale_feeder_rate(&mut self, pos: Position, factor: f32) {
+		if let Some(index) = self.world.feeder_at(pos) {
+			let rate = (self.world.feeder_period(index).get() * SecondsValue::from(factor)).max(EMITTER_PERIOD_MIN);
+			self.world.feeders_mut()[index].set_rate(seconds(rate));
+			info!("Feeder {} period: {:.3}s", index, rate);
+		}
+	}
+
+	fn scale_feeder_power(&mut self, pos: Position, factor: f32) {
+		if let Some(index) = self.world.feeder_at(pos) {
+			let feeder = &mut self.world.feeders_mut()[index];
+			let power = (feeder.power() * factor).min(EMITTER_POWER_MAX);
+			feeder.set_power(power);
+			info!("Feeder {} power: {:.2}", index, power);
+		}
+	}
+
 	fn primary_fire(&mut self, bullet_speed: f32, rate: SecondsValue) {
 		// forwards the message to the bus
 		self.bus.post(Event::PrimaryFire(bullet_speed, rate).into());
@@ -483,14 +889,14 @@ impl App {
 	}
 
 	pub fn save_gene_pool_to_file(&self) {
-		match self.world.dump(&self.saved_state_dir) {
+		match self.world.dump(&self.saved_state_dir, self.last_saved.as_deref()) {
 			Err(_) => error!("Failed to save gene pool"),
 			Ok(path) => info!("Saved {:?}", path),
 		}
 	}
 
 	pub fn save_world_to_file(&mut self) {
-		let result = self.world.serialize(&self.saved_state_dir);
+		let result = self.world.serialize(&self.saved_state_dir, self.snapshot_format);
 		match result {
 			Err(_) => error!("Failed to save world state"),
 			Ok(path) => {
@@ -498,11 +904,37 @@ impl App {
 				self.set_last_saved(path);
 			}
 		}
+		self.save_family_tree();
+	}
+
+	pub fn save_family_tree(&self) {
+		for file_name in &self.family_tree_files {
+			match self.world.family_tree().save(file_name) {
+				Err(_) => error!("Failed to save family tree to {:?}", file_name),
+				Ok(_) => info!("Saved {:?}", file_name),
+			}
+		}
 	}
 
 	fn set_last_saved(&mut self, name: path::PathBuf) { self.last_saved = Some(name) }
 
 	pub fn interact(&mut self, e: Event) {
+		if self.replay.is_some() {
+			// user input is ignored during a replay, except for quitting
+			if let Event::AppQuit = e {
+				self.quit()
+			}
+		} else {
+			self.apply(e)
+		}
+	}
+
+	fn apply(&mut self, e: Event) {
+		if let Some(ref mut journal) = self.journal {
+			if let Err(e) = journal.event(self.simulations_count, e) {
+				error!("Unable to record event: {}", e);
+			}
+		}
 		self.bus.post(e.into());
 		self.on_app_event(e)
 	}
@@ -515,16 +947,88 @@ impl App {
 		self.systems.clear();
 		self.world.clear();
 		if let Some(ref world_file) = self.last_saved {
-			let load_status = world::persist::Serializer::load(&world_file, &mut self.world);
-			if load_status.is_err() {
-				warn!("Unable to load world from checkpoint. The checkpoint was not found.");
+			if let Err(e) = world::persist::Serializer::load(world_file, &mut self.world) {
+				warn!("Unable to load world from checkpoint {:?}: {}", world_file, e);
 			}
 		};
+		self.systems.init(&self.world);
 		self.bus.post(world::alert::Alert::RestartFromCheckpoint.into())
 	}
 
 	pub fn is_running(&self) -> bool { self.is_running }
 
+	pub fn world(&self) -> &world::World { &self.world }
+
+	pub fn record_stats(&mut self, mut recorder: StatsRecorder) {
+		recorder.attach(&mut self.bus);
+		self.stats = Some(recorder);
+	}
+
+	pub fn set_options(&mut self, options: RunOptions) {
+		if let Some(stats) = options.stats {
+			self.record_stats(stats);
+		}
+		self.family_tree_files = options.family_tree;
+		if let Some(threshold) = options.species_threshold {
+			self.world.species_mut().set_threshold(threshold);
+		}
+		if let Some(scenario) = options.scenario {
+			if self.last_saved.is_none() {
+				scenario.apply(&mut self.world);
+			} else {
+				warn!("Scenario ignored, the world was restored from a snapshot");
+			}
+		}
+		if let Some(selection) = options.selection {
+			self.world.set_minion_selection(selection);
+		}
+		self.journal = options.journal;
+		self.snapshot_format = options.snapshot_format;
+		#[cfg(feature = "graphics")]
+		{
+			self.final_frame = options.final_frame;
+			self.timelapse = options.timelapse;
+		}
+		self.config_source = options.config_source;
+		if let Some(mut replay) = options.replay {
+			replay.restore(&mut self.world);
+			self.replay = Some(replay);
+		}
+	}
+
+	pub fn is_replay_finished(&self) -> bool { self.replay.as_ref().map(Replay::is_finished).unwrap_or(false) }
+
+	fn end_replay(&mut self) {
+		info!("End of replay after {} steps", self.simulations_count);
+		self.replay = None;
+		self.is_paused = true;
+	}
+
+	/// Sends the events and applies the configuration recorded before the
+	/// current step, and returns its recorded length
+	fn replay_step(&mut self) -> Option<Seconds> {
+		let step = self.simulations_count;
+		let (events, config, dt) = match self.replay {
+			Some(ref mut replay) => (replay.events(step), replay.config(step), replay.dt(step)),
+			None => return None,
+		};
+		for e in events {
+			self.apply(e);
+		}
+		// the journal holds the configuration loaded on Event::Reload, the file
+		// may have changed since
+		self.config_reload = false;
+		if let Some(config) = config {
+			self.apply_config(config);
+		}
+		if dt.is_none() {
+			self.end_replay();
+		}
+		dt
+	}
+
+	pub fn extinctions(&self) -> usize { self.world.extinctions() }
+
 	pub fn is_capturing(&self) -> bool { self.is_capturing }
 
 	pub fn on_input_event(&mut self, e: &input::Event) { self.input_state.event(e); }
@@ -543,6 +1047,7 @@ impl App {
 		self.viewport = Viewport::rect(width, height, self.viewport.scale);
 	}
 
+	#[cfg(feature = "graphics")]
 	fn from_transform(transform: &Transform) -> Matrix4<f32> {
 		use cgmath::Rotation3;
 		let position = transform.position;
@@ -554,22 +1059,19 @@ impl App {
 	}
 
 	pub fn init(&mut self, mode: SystemMode) {
+		// a replay runs the same systems as the recorded session
+		let mode = self.replay.as_ref().map(Replay::mode).unwrap_or(mode);
+		if let Some(ref mut journal) = self.journal {
+			if let Err(e) = journal.start(&self.world, mode) {
+				error!("Unable to record the start of the session: {}", e);
+			}
+		}
 		self.init_systems(mode);
 		self.register_all();
 		self.bus.post(world::alert::Alert::BeginSimulation.into());
 	}
 
-	fn register_all(&mut self) {
-		// registered() drains the list, so this can be called only once per frame
-		let found: Vec<agent::Agent> =
-			self.world.registered().iter().filter_map(|id| self.world.agent(*id)).cloned().collect();
-		self.systems.register(&found[..]);
-	}
-
-	fn cleanup_before(&mut self) {
-		self.world.cleanup_before();
-		self.systems.unregister(&self.world.sweep());
-	}
+	fn register_all(&mut self) { self.systems.register_all(&mut self.world); }
 
 	fn init_systems(&mut self, mode: SystemMode) {
 		self.systems.set_mode(mode);
@@ -577,15 +1079,6 @@ impl App {
 		self.systems.init(&self.world);
 	}
 
-	fn update_systems(&mut self, dt: Seconds) {
-		self.systems.for_each_par_write(&self.world, &|s, world| s.step(&world, dt));
-		self.systems.for_each_read(&mut self.world, &self.bus, &|s, mut world, outbox| s.apply(&mut world, outbox));
-	}
-
-	fn cleanup_after(&mut self) { self.register_all(); }
-
-	fn tick(&mut self, dt: Seconds) { self.world.tick(dt); }
-
 	pub fn receive(&mut self) {
 		for event in self.reply_inbox.drain() {
 			if let Message::Event(event) = event {
@@ -632,6 +1125,9 @@ impl App {
 
 		self.update_input::<DefaultController>(frame_time_smooth);
 		self.receive();
+		if self.is_replay_finished() {
+			self.end_replay();
+		}
 		let speed_factor = if self.is_paused { 0.0 } else { self.speed_factors.get() };
 		let quantum = quantum_target.unwrap_or_else(|| num::clamp(target_duration, MIN_FRAME_LENGTH, MAX_FRAME_LENGTH));
 		let (dt, rounds) = if speed_factor <= 1.0 {
@@ -660,11 +1156,75 @@ impl App {
 		}
 	}
 
+	/// Polls the configuration file for changes
+	fn is_config_modified(&mut self) -> bool {
+		let now = self.wall_clock.seconds().get();
+		if now - self.config_checked < CONFIG_POLL_INTERVAL {
+			return false;
+		}
+		self.config_checked = now;
+		self.config_source.as_ref().map(ConfigSource::is_modified).unwrap_or(false)
+	}
+
+	/// Reloads the configuration, keeping the current one if it is invalid
+	fn reload_config(&mut self) {
+		self.config_reload = false;
+		let config = match self.config_source {
+			None => {
+				info!("No configuration file to reload");
+				return;
+			}
+			Some(ref mut source) => match source.load() {
+				Ok(config) => config,
+				Err(e) => {
+					error!("{}", e);
+					return;
+				}
+			},
+		};
+		self.apply_config(config);
+	}
+
+	/// Records the configuration, as the file may change again before a replay
+	fn apply_config(&mut self, config: SimulationConfig) {
+		if let Some(ref mut journal) = self.journal {
+			if let Err(e) = journal.config(self.simulations_count, &config) {
+				error!("Unable to record configuration: {}", e);
+			}
+		}
+		let changes = self.world.config().changes(&config);
+		if changes.is_empty() {
+			info!("Configuration reloaded, no changes");
+		}
+		for name in &changes {
+			match SimulationConfig::scope(name) {
+				Scope::Live => info!("Configuration reloaded: {} changed", name),
+				Scope::NewAgents =>
+					info!("Configuration reloaded: {} changed, for the agents created from now on", name),
+				Scope::NewWorld =>
+					warn!("Configuration reloaded: {} changed, but only applies to a new world (-n)", name),
+			}
+		}
+		self.world.set_config(config);
+	}
+
 	pub fn simulate(&mut self, dt: Seconds) -> SimulationUpdate {
-		self.cleanup_before();
-		self.update_systems(dt);
-		self.cleanup_after();
-		self.tick(dt);
+		// a replay only applies the configurations found in its journal
+		if self.replay.is_none() && (self.config_reload || self.is_config_modified()) {
+			self.reload_config();
+		}
+		let dt = self.replay_step().unwrap_or(dt);
+		if let Some(ref mut journal) = self.journal {
+			if let Err(e) = journal.step(self.simulations_count, dt) {
+				error!("Unable to record step: {}", e);
+			}
+		}
+		self.systems.simulate(&mut self.world, &self.bus, dt);
+		if let Some(ref mut stats) = self.stats {
+			if let Err(e) = stats.update(&self.world) {
+				error!("Unable to record stats: {}", e);
+			}
+		}
 
 		self.simulations_count += 1;
 
@@ -675,6 +1235,8 @@ impl App {
 			elapsed: self.world.seconds(),
 			population: self.world.agents(agent::AgentType::Minion).len(),
 			extinctions: self.world.extinctions(),
+			species: self.world.species().sizes(),
+			conditions: self.world.conditions(),
 		}
 	}
 }
@@ -1,4 +1,5 @@
 use super::*;
+use backend::world::species;
 use frontend::render;
 use frontend::render::Draw;
 use frontend::render::Style;
@@ -72,30 +73,59 @@ impl App {
 			for (_, agent) in swarm.agents().iter() {
 				let energy_left = agent.state.energy_ratio();
 				let phase = agent.state.phase();
+				let tint = if self.is_species_tint { self.world.species().species_of(agent.id()) } else { None };
+				// agents on the edge of a wrapping world are also seen on the opposite side
+				let mut offsets = vec![Position::new(0., 0.)];
+				offsets.extend(self.world.boundary.images(
+					&self.world.extent,
+					agent.transform().position,
+					BOUNDARY_IMAGE_MARGIN,
+				));
 				for segment in agent.segments() {
 					let body_transform = Self::from_transform(&segment.transform());
 
 					let mesh = &segment.mesh();
 					let fixture_scale = Matrix4::from_scale(segment.growing_radius());
-					let transform = body_transform * fixture_scale;
 
-					let appearance = render::Appearance::new(segment.color(), [energy_left, phase, 0., 0.]);
+					let color = match tint {
+						Some(species_id) => segment.tinted_color(species::tint(species_id)),
+						None => segment.color(),
+					};
+					let appearance = render::Appearance::new(color, [energy_left, phase, 0., 0.]);
 
-					match mesh.shape {
-						obj::Shape::Ball { .. } => {
-							batch_buffer.draw_ball(None, transform, appearance);
-						}
-						obj::Shape::Star { .. } => {
-							batch_buffer.draw_star(Some(Style::Lit), transform, &mesh.vertices[..], appearance);
-						}
-						obj::Shape::Poly { .. } => {
-							batch_buffer.draw_star(Some(Style::Lit), transform, &mesh.vertices[..], appearance);
-						}
-						obj::Shape::Box { ratio, .. } => {
-							batch_buffer.draw_quad(Some(Style::Lit), transform, ratio, appearance);
-						}
-						obj::Shape::Triangle { .. } => {
-							batch_buffer.draw_triangle(Some(Style::Lit), transform, &mesh.vertices[0..3], appearance);
+					for offset in &offsets {
+						let transform = Matrix4::from_translation(offset.extend(0.)) * body_transform * fixture_scale;
+						match mesh.shape {
+							obj::Shape::Ball { .. } => {
+								batch_buffer.draw_ball(None, transform, appearance.clone());
+							}
+							obj::Shape::Star { .. } => {
+								batch_buffer.draw_star(
+									Some(Style::Lit),
+									transform,
+									&mesh.vertices[..],
+									appearance.clone(),
+								);
+							}
+							obj::Shape::Poly { .. } => {
+								batch_buffer.draw_star(
+									Some(Style::Lit),
+									transform,
+									&mesh.vertices[..],
+									appearance.clone(),
+								);
+							}
+							obj::Shape::Box { ratio, .. } => {
+								batch_buffer.draw_quad(Some(Style::Lit), transform, ratio, appearance.clone());
+							}
+							obj::Shape::Triangle { .. } => {
+								batch_buffer.draw_triangle(
+									Some(Style::Lit),
+									transform,
+									&mesh.vertices[0..3],
+									appearance.clone(),
+								);
+							}
 						}
 					}
 				}
@@ -108,14 +138,12 @@ impl App {
 	where R: render::Draw {
 		use cgmath::SquareMatrix;
 		let extent = &self.world.extent;
-		let points = &[
-			extent.min,
-			Position::new(extent.min.x, extent.max.y),
-			extent.max,
-			Position::new(extent.max.x, extent.min.y),
-			extent.min,
-		];
-		renderer.draw_lines(None, Matrix4::identity(), points, render::Appearance::rgba(self.lights.get()));
+		// a wrapping world has no walls, but its edges are still outlined
+		let mut points = self.world.boundary.walls(extent).unwrap_or_else(|| {
+			vec![extent.bottom_left(), extent.bottom_right(), extent.top_right(), extent.top_left()]
+		});
+		points.push(points[0]);
+		renderer.draw_lines(None, Matrix4::identity(), &points, render::Appearance::rgba(self.lights.get()));
 		renderer.draw_quad(
 			Some(Style::Stage),
 			Matrix4::from_scale(extent.max.x - extent.min.x),
@@ -210,6 +238,47 @@ impl App {
 		};
 	}
 
+	/// Paints the current frame on the CPU
+	pub fn paint_software(&self, renderer: &mut render::SoftwareRenderer, camera: &render::Camera) {
+		let environment = self.environment();
+		renderer.setup_frame(camera, environment.background_color, &environment.lights);
+		renderer.begin_frame();
+		self.paint(renderer);
+	}
+
+	/// Saves the frame requested with --png, if any, as seen by the camera
+	pub fn save_final_frame(&self) {
+		if let Some(ref file_path) = self.final_frame {
+			let mut renderer = render::SoftwareRenderer::new(self.viewport.width(), self.viewport.height());
+			let camera = render::Camera::ortho(self.camera.position(), self.viewport.scale, self.viewport.ratio);
+			self.paint_software(&mut renderer, &camera);
+			match renderer.save_png(file_path) {
+				Ok(()) => info!("Saved {:?}", file_path),
+				Err(e) => error!("Unable to save {:?}: {}", file_path, e),
+			}
+		}
+	}
+
+	/// Saves a timelapse frame, if one is due at the given step
+	pub fn record_timelapse(&mut self, step: usize) {
+		let (file_path, mut renderer, camera) = match self.timelapse {
+			Some(ref mut timelapse) if timelapse.is_due(step) => {
+				let ratio = timelapse.width() as f32 / timelapse.height() as f32;
+				let (center, width) = timelapse.framing().camera(&self.world.extent, ratio);
+				(
+					timelapse.next_frame(),
+					render::SoftwareRenderer::new(timelapse.width(), timelapse.height()),
+					render::Camera::ortho(center, width, ratio),
+				)
+			}
+			_ => return,
+		};
+		self.paint_software(&mut renderer, &camera);
+		if let Err(e) = renderer.save_png(&file_path) {
+			error!("Unable to save {:?}: {}", file_path, e);
+		}
+	}
+
 	pub fn paint<R>(&self, renderer: &mut R)
 	where R: render::Draw + render::DrawBatch + render::DrawBuffer {
 		self.paint_feeders(renderer);
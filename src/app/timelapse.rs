@@ -0,0 +1,90 @@
+//! Frames saved at regular intervals during a headless run, rendered in
+//! software
+use app::constants::*;
+use core::geometry::{Position, Rect};
+use std::fs;
+use std::io;
+use std::path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Framing {
+	/// The whole world, whatever its extent
+	Extent,
+	/// A fixed window on the world, of the given width in world units
+	Window { center: Position, width: f32 },
+}
+
+impl Framing {
+	/// `extent`, or `x,y,width`
+	pub fn parse(s: &str) -> Option<Self> {
+		if s == "extent" {
+			return Some(Framing::Extent);
+		}
+		let values = s.split(',').map(|v| v.trim().parse::<f32>().ok()).collect::<Option<Vec<_>>>()?;
+		match values.as_slice() {
+			[x, y, width] if *width > 0. => Some(Framing::Window { center: Position::new(*x, *y), width: *width }),
+			_ => None,
+		}
+	}
+
+	/// Center and width of the camera for an image of the given aspect ratio
+	pub fn camera(&self, extent: &Rect, ratio: f32) -> (Position, f32) {
+		match *self {
+			Framing::Extent => {
+				let center = Position::new((extent.min.x + extent.max.x) / 2., (extent.min.y + extent.max.y) / 2.);
+				let width = (extent.max.x - extent.min.x).max((extent.max.y - extent.min.y) * ratio);
+				(center, width)
+			}
+			Framing::Window { center, width } => (center, width),
+		}
+	}
+}
+
+/// The highest number of the frames in a directory, or 0 if there are none
+fn last_frame(dir: &path::Path) -> io::Result<usize> {
+	let mut last = 0;
+	for entry in fs::read_dir(dir)? {
+		let file_name = entry?.file_name();
+		let number = file_name
+			.to_str()
+			.and_then(|name| name.strip_prefix(CAPTURE_FILENAME_PREFIX))
+			.and_then(|name| name.strip_suffix(".png"))
+			.and_then(|number| number.parse::<usize>().ok());
+		last = last.max(number.unwrap_or(0));
+	}
+	Ok(last)
+}
+
+pub struct Timelapse {
+	dir: path::PathBuf,
+	interval: usize,
+	width: u32,
+	height: u32,
+	framing: Framing,
+	frames: usize,
+}
+
+impl Timelapse {
+	/// Frames are saved in the given directory, which is created if needed,
+	/// every `interval` simulation steps. Their numbers carry on from the
+	/// frames already there, so that a resumed run does not overwrite them
+	pub fn create(dir: &path::Path, interval: usize, width: u32, height: u32, framing: Framing) -> io::Result<Self> {
+		fs::create_dir_all(dir)?;
+		let frames = last_frame(dir)?;
+		Ok(Timelapse { dir: dir.to_owned(), interval: interval.max(1), width, height, framing, frames })
+	}
+
+	pub fn width(&self) -> u32 { self.width }
+
+	pub fn height(&self) -> u32 { self.height }
+
+	pub fn framing(&self) -> Framing { self.framing }
+
+	pub fn is_due(&self, step: usize) -> bool { step % self.interval == 0 }
+
+	/// The file name of the next frame
+	pub fn next_frame(&mut self) -> path::PathBuf {
+		self.frames += 1;
+		self.dir.join(format!("{}{:08}.png", CAPTURE_FILENAME_PREFIX, self.frames))
+	}
+}
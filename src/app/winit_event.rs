@@ -89,6 +89,11 @@ impl input::EventMapper<winit::WindowEvent> for WinitEventMapper {
 				Equals -> Plus,
 				Subtract -> Minus,
 				Space -> Space,
+				Delete -> Del,
+				LBracket -> OpenBracket,
+				RBracket -> CloseBracket,
+				Semicolon -> Semicolon,
+				Apostrophe -> Apostrophe,
 				Escape -> Esc
 			]
 		}
@@ -0,0 +1,208 @@
+//! Simulation parameters which can be changed without rebuilding. They are
+//! read from TOML or JSON files, where any of them can be left out, and saved
+//! with the snapshots
+use app::constants::*;
+use backend::world::boundary::Boundary;
+use backend::world::environment::Schedule;
+use core::clock::SecondsValue;
+use serde_json;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path;
+use std::time;
+use toml;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+	/// Half the side of the square world. Only affects new worlds
+	pub world_radius: f32,
+	/// What happens at the edge of the world: kill, bounce, wrap or circle.
+	/// Only affects new worlds
+	pub boundary: Boundary,
+	/// Number of feeders, evenly spaced on a circle. Only affects new worlds
+	pub emitter_count: usize,
+	/// Radius of the circle of feeders. Only affects new worlds
+	pub emitter_distance: f32,
+	/// Seconds between two resources dropped by the feeders which have no
+	/// period of their own
+	pub emitter_period: SecondsValue,
+	pub emitter_intensity_decay: f32,
+	/// Energy, as a fraction of the maximum, a mature minion needs to lay a
+	/// spore
+	pub spawn_cost_threshold: f32,
+	/// Fraction of its energy a minion spends laying a spore
+	pub spawn_cost_ratio: f32,
+	/// Fraction of its energy a minion spends growing, and by which it grows
+	pub growth_cost_ratio: f32,
+	/// Energy lost by a minion running into anything but a resource
+	pub collision_base_cost: f32,
+	pub density_minion: f32,
+	pub density_resource: f32,
+	pub density_spore: f32,
+	pub density_player: f32,
+	/// Linear damping of all the agents except the player
+	pub linear_damping: f32,
+	pub angular_damping: f32,
+	/// Reduction of the thrust of the player, per square unit of speed
+	pub drag_coefficient: f32,
+	/// Maximum number of bits flipped by a mutation, per byte of DNA
+	pub mutation_rate: f32,
+	/// Seasons, famines and booms. Last, as it is a table of its own in TOML
+	pub environment: Schedule,
+}
+
+/// When a change to a parameter takes effect in a running world
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+	/// At the next simulation step
+	Live,
+	/// On the agents created from then on
+	NewAgents,
+	/// Only when starting a new world
+	NewWorld,
+}
+
+impl Default for SimulationConfig {
+	fn default() -> Self {
+		SimulationConfig {
+			world_radius: WORLD_RADIUS,
+			boundary: Boundary::default(),
+			emitter_count: EMITTER_COUNT,
+			emitter_distance: EMITTER_DISTANCE,
+			emitter_period: EMITTER_PERIOD,
+			emitter_intensity_decay: EMITTER_INTENSITY_DECAY,
+			spawn_cost_threshold: SPAWN_COST_THRESHOLD,
+			spawn_cost_ratio: SPAWN_COST_RATIO,
+			growth_cost_ratio: GROWTH_COST_RATIO,
+			collision_base_cost: COLLISION_BASE_COST,
+			density_minion: DENSITY_MINION,
+			density_resource: DENSITY_RESOURCE,
+			density_spore: DENSITY_SPORE,
+			density_player: DENSITY_PLAYER,
+			linear_damping: LINEAR_DAMPING_DEFAULT,
+			angular_damping: ANGULAR_DAMPING,
+			drag_coefficient: DRAG_COEFFICIENT,
+			mutation_rate: MUTATION_RATE,
+			environment: Schedule::default(),
+		}
+	}
+}
+
+fn invalid_data<E>(e: E) -> io::Error
+where E: ToString {
+	io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+fn is_toml(file_path: &path::Path) -> bool { file_path.extension().and_then(OsStr::to_str) == Some("toml") }
+
+impl SimulationConfig {
+	pub fn scope(name: &str) -> Scope {
+		match name {
+			"world_radius" | "boundary" | "emitter_count" | "emitter_distance" => Scope::NewWorld,
+			"emitter_intensity_decay"
+			| "density_minion"
+			| "density_resource"
+			| "density_spore"
+			| "density_player"
+			| "linear_damping"
+			| "angular_damping" => Scope::NewAgents,
+			_ => Scope::Live,
+		}
+	}
+
+	/// Names of the parameters which differ between two configurations
+	pub fn changes(&self, other: &SimulationConfig) -> Vec<String> {
+		match (serde_json::to_value(self), serde_json::to_value(other)) {
+			(Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) => before
+				.iter()
+				.filter(|(name, value)| after.get(*name) != Some(value))
+				.map(|(name, _)| name.clone())
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// TOML for .toml files, JSON otherwise
+	pub fn load(file_path: &path::Path) -> io::Result<Self> {
+		let text = fs::read_to_string(file_path)?;
+		if is_toml(file_path) {
+			toml::from_str(&text).map_err(invalid_data)
+		} else {
+			serde_json::from_str(&text).map_err(invalid_data)
+		}
+	}
+
+	/// TOML for .toml files, JSON otherwise
+	pub fn save(&self, file_path: &path::Path) -> io::Result<()> {
+		let text = if is_toml(file_path) {
+			toml::to_string_pretty(self).map_err(invalid_data)?
+		} else {
+			serde_json::to_string_pretty(self)?
+		};
+		fs::write(file_path, text)
+	}
+
+	/// Overrides a single parameter, given as `name=value`
+	pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+		let mut parts = assignment.splitn(2, '=');
+		let (name, value) = match (parts.next(), parts.next()) {
+			(Some(name), Some(value)) => (name.trim(), value.trim()),
+			_ => return Err(format!("Expected name=value: {}", assignment)),
+		};
+		let mut fields = match serde_json::to_value(&*self) {
+			Ok(serde_json::Value::Object(fields)) => fields,
+			_ => return Err("Could not read the configuration".to_owned()),
+		};
+		if !fields.contains_key(name) {
+			return Err(format!("Unknown parameter: {}", name));
+		}
+		let value = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+		fields.insert(name.to_owned(), value);
+		*self = serde_json::from_value(serde_json::Value::Object(fields))
+			.map_err(|e| format!("Invalid value for {}: {}", name, e))?;
+		Ok(())
+	}
+}
+
+/// A configuration file and the overrides applied on top of it, which can be
+/// read again when the file changes
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSource {
+	file_path: Option<path::PathBuf>,
+	overrides: Vec<String>,
+	modified: Option<time::SystemTime>,
+}
+
+impl ConfigSource {
+	pub fn new(file_path: Option<path::PathBuf>, overrides: Vec<String>) -> Self {
+		ConfigSource { file_path, overrides, modified: None }
+	}
+
+	pub fn file_path(&self) -> Option<&path::Path> { self.file_path.as_deref() }
+
+	fn last_modified(&self) -> Option<time::SystemTime> {
+		self.file_path.as_ref().and_then(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+	}
+
+	/// Whether the file has changed since it was last loaded
+	pub fn is_modified(&self) -> bool {
+		self.file_path.is_some() && self.last_modified().map(|t| Some(t) != self.modified).unwrap_or(false)
+	}
+
+	/// The defaults, overridden by the file, if any, and then by each override
+	pub fn load(&mut self) -> Result<SimulationConfig, String> {
+		let mut config = match self.file_path {
+			Some(ref file_path) => {
+				self.modified = self.last_modified();
+				SimulationConfig::load(file_path).map_err(|e| format!("Unable to load {:?}: {}", file_path, e))?
+			}
+			None => SimulationConfig::default(),
+		};
+		for assignment in &self.overrides {
+			config.set(assignment)?;
+		}
+		Ok(config)
+	}
+}
@@ -0,0 +1,138 @@
+//! Offline analysis of a snapshot, without restoring it into a running world
+use backend::config::SimulationConfig;
+use backend::obj::Id;
+use backend::stats::Distribution;
+use backend::world::species::SpeciesId;
+use core::clock::SecondsValue;
+use core::rng::Seed;
+use serde_json;
+use std::collections::BTreeMap;
+use std::io;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Extent {
+	pub left: f32,
+	pub bottom: f32,
+	pub right: f32,
+	pub top: f32,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GenePool {
+	pub size: usize,
+	/// Index of the next genome handed out in round-robin order
+	pub index: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GenomeCount {
+	/// Base64, as in gene pool files
+	pub dna: String,
+	pub count: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SpeciesSummary {
+	pub id: SpeciesId,
+	pub representative: String,
+	pub members: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AgentSummary {
+	pub id: Id,
+	pub agent_type: String,
+	pub age: SecondsValue,
+	pub generation: usize,
+	pub energy: f32,
+	pub dna: String,
+}
+
+/// Species are regrouped from the minions in the snapshot, the way a resumed
+/// run would at its first clustering
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Report {
+	pub version: u32,
+	pub seed: Option<Seed>,
+	pub time: SecondsValue,
+	/// None for snapshots saved before the configuration was
+	pub config: Option<SimulationConfig>,
+	pub extent: Extent,
+	pub counts: BTreeMap<String, usize>,
+	pub minion_gene_pool: GenePool,
+	pub resource_gene_pool: GenePool,
+	pub minion_energy: Distribution,
+	pub common_genomes: Vec<GenomeCount>,
+	pub species_count: usize,
+	pub species: Vec<SpeciesSummary>,
+	pub oldest: Vec<AgentSummary>,
+}
+
+impl Report {
+	pub fn write_json<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		serde_json::to_writer_pretty(&mut *out, self)?;
+		writeln!(out)
+	}
+
+	pub fn write_text<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		match self.seed {
+			Some(seed) => writeln!(out, "Snapshot version {}, seed {}, at {:.1}s", self.version, seed, self.time)?,
+			None => writeln!(out, "Snapshot version {}, at {:.1}s", self.version, self.time)?,
+		}
+		let e = &self.extent;
+		writeln!(out, "Extent: ({}, {}) - ({}, {})", e.left, e.bottom, e.right, e.top)?;
+		if let Some(serde_json::Value::Object(ref config)) =
+			self.config.as_ref().and_then(|c| serde_json::to_value(c).ok())
+		{
+			writeln!(out)?;
+			writeln!(out, "Configuration:")?;
+			for (name, value) in config {
+				writeln!(out, "  {:<24}{}", name, value)?;
+			}
+		}
+		writeln!(out)?;
+		writeln!(out, "Agents:")?;
+		for (agent_type, count) in &self.counts {
+			writeln!(out, "  {:<16}{:>8}", agent_type, count)?;
+		}
+		writeln!(out)?;
+		writeln!(
+			out,
+			"Minion gene pool: {} genomes, next {}",
+			self.minion_gene_pool.size, self.minion_gene_pool.index
+		)?;
+		writeln!(
+			out,
+			"Resource gene pool: {} genomes, next {}",
+			self.resource_gene_pool.size, self.resource_gene_pool.index
+		)?;
+		let energy = &self.minion_energy;
+		writeln!(
+			out,
+			"Minion energy: mean {:.1}, p10 {:.1}, p50 {:.1}, p90 {:.1}",
+			energy.mean, energy.p10, energy.p50, energy.p90
+		)?;
+		writeln!(out)?;
+		writeln!(out, "Most common genomes:")?;
+		for genome in &self.common_genomes {
+			writeln!(out, "  {:>6}  {}", genome.count, genome.dna)?;
+		}
+		writeln!(out)?;
+		writeln!(out, "Species: {}", self.species_count)?;
+		for species in &self.species {
+			writeln!(out, "  #{:<5}{:>6}  {}", species.id, species.members, species.representative)?;
+		}
+		writeln!(out)?;
+		writeln!(out, "Oldest agents:")?;
+		for agent in &self.oldest {
+			writeln!(
+				out,
+				"  {:<16}{:>8}  age {:>8.1}s  generation {:>4}  energy {:>6.1}  {}",
+				agent.agent_type, agent.id, agent.age, agent.generation, agent.energy, agent.dna
+			)?;
+		}
+		Ok(())
+	}
+}
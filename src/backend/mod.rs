@@ -1,4 +1,9 @@
+pub mod config;
+pub mod inspect;
 pub mod messagebus;
 pub mod obj;
+pub mod simulation;
+pub mod stats;
+pub mod svg;
 pub mod systems;
 pub mod world;
@@ -3,6 +3,7 @@ use core::color;
 use core::geometry::Transform;
 use core::geometry::*;
 use std::f32::consts::*;
+use std::fmt;
 
 pub type Rgba = color::Rgba<f32>;
 
@@ -55,6 +56,22 @@ impl Shape {
 	pub fn mid(&self) -> isize { self.length() as isize / 2 }
 }
 
+impl fmt::Display for Shape {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Shape::Ball { radius } => write!(f, "ball r={:.2}", radius),
+			Shape::Box { radius, ratio } => write!(f, "box r={:.2} ratio={:.2}", radius, ratio),
+			Shape::Star { radius, n, ratio1, ratio2 } => {
+				write!(f, "star n={} r={:.2} ratio1={:.2} ratio2={:.2}", n, radius, ratio1, ratio2)
+			}
+			Shape::Poly { radius, n } => write!(f, "poly n={} r={:.2}", n, radius),
+			Shape::Triangle { radius, angle1, angle2 } => {
+				write!(f, "triangle r={:.2} angle1={:.2} angle2={:.2}", radius, angle1, angle2)
+			}
+		}
+	}
+}
+
 #[derive(Clone, Copy)]
 pub enum Winding {
 	CW = 1,
@@ -0,0 +1,97 @@
+//! Batch driver for a world and its systems, with no front end attached
+use backend::messagebus::{Inbox, Message, PubSub, Whiteboard};
+use backend::obj::Id;
+use backend::stats::StatsRecorder;
+use backend::systems::{SystemMode, Systems};
+use backend::world;
+use backend::world::agent::{Agent, AgentMap, AgentType};
+use core::clock::Seconds;
+use core::resource::ResourceLoader;
+use core::rng::Seed;
+use std::io;
+use std::path;
+
+pub struct Simulation {
+	world: world::World,
+	bus: PubSub,
+	systems: Systems,
+	stats: Option<StatsRecorder>,
+	steps: usize,
+}
+
+impl Simulation {
+	/// Attaches the batch systems to an existing world
+	pub fn new(world: world::World) -> Self { Self::with_mode(world, SystemMode::Batch) }
+
+	pub fn with_mode(mut world: world::World, mode: SystemMode) -> Self {
+		let mut bus = PubSub::new();
+		let mut systems = Systems::new(mode);
+		systems.attach(&mut bus);
+		systems.init(&world);
+		systems.register_all(&mut world);
+		Simulation { world, bus, systems, stats: None, steps: 0 }
+	}
+
+	/// A new world, populated from the minion gene pool found by the resource
+	/// loader
+	pub fn from_gene_pool<R>(res: &R, minion_gene_pool: &str, seed: Seed) -> Self
+	where R: ResourceLoader<u8> {
+		Self::new(world::World::new(res, minion_gene_pool, seed))
+	}
+
+	/// A world restored from a snapshot file. The gene pool is only used to
+	/// repopulate the world after an extinction
+	pub fn from_snapshot<R>(res: &R, minion_gene_pool: &str, snapshot: &path::Path) -> io::Result<Self>
+	where R: ResourceLoader<u8> {
+		let mut world = world::World::new(res, minion_gene_pool, 0);
+		world::persist::Serializer::load(snapshot, &mut world)?;
+		Ok(Self::new(world))
+	}
+
+	pub fn save(&self, snapshot: &path::Path) -> io::Result<()> {
+		world::persist::Serializer::save(snapshot, &self.world)
+	}
+
+	/// Advances the world by a single step of length dt
+	pub fn step(&mut self, dt: Seconds) {
+		self.systems.simulate(&mut self.world, &self.bus, dt);
+		self.steps += 1;
+		if let Some(ref mut stats) = self.stats {
+			if let Err(e) = stats.update(&self.world) {
+				error!("Unable to record stats: {}", e);
+			}
+		}
+	}
+
+	pub fn record_stats(&mut self, mut recorder: StatsRecorder) {
+		recorder.attach(&mut self.bus);
+		self.stats = Some(recorder);
+	}
+
+	pub fn run(&mut self, dt: Seconds, steps: usize) {
+		for _ in 0..steps {
+			self.step(dt)
+		}
+	}
+
+	pub fn steps(&self) -> usize { self.steps }
+
+	pub fn seconds(&self) -> Seconds { self.world.seconds() }
+
+	pub fn population(&self) -> usize { self.world.agents(AgentType::Minion).len() }
+
+	pub fn agent(&self, id: Id) -> Option<&Agent> { self.world.agent(id) }
+
+	pub fn agents(&self, agent_type: AgentType) -> &AgentMap { self.world.agents(agent_type) }
+
+	pub fn world(&self) -> &world::World { &self.world }
+
+	/// Agents added through the world are picked up by the systems at the next
+	/// step
+	pub fn world_mut(&mut self) -> &mut world::World { &mut self.world }
+
+	/// Receives the messages posted by the systems which match the filter
+	pub fn subscribe(&mut self, accept: Box<dyn Fn(&Message) -> bool>) -> Inbox { self.bus.subscribe(accept) }
+
+	pub fn into_world(self) -> world::World { self.world }
+}
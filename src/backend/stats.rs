@@ -0,0 +1,271 @@
+//! Time series of population statistics, sampled at regular simulated time
+//! intervals
+use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
+use backend::world;
+use backend::world::agent::AgentType;
+use backend::world::alert::Alert;
+use backend::world::gen;
+use backend::world::species::SpeciesId;
+use core::clock::{Seconds, SecondsValue};
+use serde_json;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatsFormat {
+	Csv,
+	JsonLines,
+}
+
+impl StatsFormat {
+	/// JSON Lines for .json and .jsonl files, CSV otherwise
+	pub fn from_path(path: &path::Path) -> Self {
+		match path.extension().and_then(OsStr::to_str) {
+			Some("json") | Some("jsonl") => StatsFormat::JsonLines,
+			_ => StatsFormat::Csv,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Distribution {
+	pub mean: f32,
+	pub p10: f32,
+	pub p50: f32,
+	pub p90: f32,
+}
+
+impl Distribution {
+	pub fn of(mut values: Vec<f32>) -> Self {
+		if values.is_empty() {
+			return Distribution::default();
+		}
+		values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+		let percentile = |p: f32| values[((values.len() - 1) as f32 * p).round() as usize];
+		Distribution {
+			mean: values.iter().sum::<f32>() / values.len() as f32,
+			p10: percentile(0.1),
+			p50: percentile(0.5),
+			p90: percentile(0.9),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Diversity {
+	pub distinct_genomes: usize,
+	/// Mean Hamming distance in bits between any two genomes
+	pub mean_distance: f32,
+}
+
+impl Diversity {
+	pub fn of(dnas: &[&gen::Dna]) -> Self {
+		let distinct_genomes = dnas.iter().collect::<BTreeSet<_>>().len();
+		let mut total = 0usize;
+		let mut pairs = 0usize;
+		for (i, a) in dnas.iter().enumerate() {
+			for b in &dnas[i + 1..] {
+				total += gen::hamming_distance(a, b);
+				pairs += 1;
+			}
+		}
+		let mean_distance = if pairs > 0 { total as f32 / pairs as f32 } else { 0. };
+		Diversity { distinct_genomes, mean_distance }
+	}
+}
+
+/// Births, deaths and fertilisations are counted since the previous sample, as
+/// are the resources emitted by each feeder, or since the feeders were last
+/// added, removed or replaced
+#[derive(Clone, Debug, Serialize)]
+pub struct Sample {
+	pub time: SecondsValue,
+	pub steps: usize,
+	pub counts: BTreeMap<String, usize>,
+	pub births: usize,
+	pub deaths: usize,
+	pub fertilised: usize,
+	pub energy: Distribution,
+	pub maturity: Distribution,
+	pub feeder_throughput: Vec<usize>,
+	pub diversity: Diversity,
+	/// Members of each species, as of the last clustering
+	pub species: BTreeMap<SpeciesId, usize>,
+}
+
+pub struct StatsRecorder {
+	format: StatsFormat,
+	out: Box<dyn io::Write>,
+	interval: Seconds,
+	next_sample: SecondsValue,
+	has_header: bool,
+	inbox: Option<Inbox>,
+	steps: usize,
+	births: usize,
+	deaths: usize,
+	fertilised: usize,
+	/// Resources emitted by each feeder as of the previous sample
+	emitted: Vec<usize>,
+	/// Revision of the feeders the counts above belong to
+	feeders_revision: Option<usize>,
+}
+
+impl StatsRecorder {
+	pub fn new<W>(out: W, format: StatsFormat, interval: Seconds) -> Self
+	where W: io::Write + 'static {
+		StatsRecorder {
+			format,
+			out: Box::new(out),
+			interval,
+			next_sample: 0.,
+			has_header: false,
+			inbox: None,
+			steps: 0,
+			births: 0,
+			deaths: 0,
+			fertilised: 0,
+			emitted: Vec::new(),
+			feeders_revision: None,
+		}
+	}
+
+	/// Writes to a new file, in the format given by its extension
+	pub fn create(file_path: &path::Path, interval: Seconds) -> io::Result<Self> {
+		let file = fs::File::create(file_path)?;
+		Ok(Self::new(io::BufWriter::new(file), StatsFormat::from_path(file_path), interval))
+	}
+
+	pub fn attach(&mut self, bus: &mut PubSub) {
+		self.inbox = Some(bus.subscribe(Box::new(|m| {
+			matches!(
+				*m,
+				Message::Alert(Alert::NewMinion) | Message::Alert(Alert::DieMinion) | Message::Alert(Alert::Fertilised)
+			)
+		})));
+	}
+
+	/// To be called after each simulation step
+	pub fn update(&mut self, world: &world::World) -> io::Result<()> {
+		self.steps += 1;
+		if let Some(ref inbox) = self.inbox {
+			for message in inbox.drain() {
+				match message {
+					Message::Alert(Alert::NewMinion) => self.births += 1,
+					Message::Alert(Alert::DieMinion) => self.deaths += 1,
+					Message::Alert(Alert::Fertilised) => self.fertilised += 1,
+					_ => {}
+				}
+			}
+		}
+		// the counts of the previous feeders, such as those of a restored
+		// snapshot, are no baseline for the new ones
+		if self.feeders_revision != Some(world.feeders_revision()) {
+			self.feeders_revision = Some(world.feeders_revision());
+			self.emitted = world.feeders().iter().map(world::Feeder::emitted).collect();
+		}
+		let now = world.seconds().get();
+		if now >= self.next_sample {
+			self.next_sample = now + self.interval.get();
+			let sample = self.sample(world);
+			self.write(&sample)?;
+			self.births = 0;
+			self.deaths = 0;
+			self.fertilised = 0;
+			self.emitted = world.feeders().iter().map(world::Feeder::emitted).collect();
+		}
+		Ok(())
+	}
+
+	pub fn sample(&self, world: &world::World) -> Sample {
+		let minions = world.agents(AgentType::Minion);
+		let counts = AgentType::all().iter().map(|t| (t.to_string(), world.agents(*t).len())).collect();
+		let energy = Distribution::of(minions.values().map(|a| a.state.energy()).collect());
+		let maturity =
+			Distribution::of(minions.values().filter_map(|a| a.segment(0)).map(|s| s.state.maturity()).collect());
+		let feeder_throughput = world
+			.feeders()
+			.iter()
+			.enumerate()
+			.map(|(i, f)| f.emitted().saturating_sub(self.emitted.get(i).cloned().unwrap_or(0)))
+			.collect();
+		let dnas = minions.values().map(|a| a.dna()).collect::<Vec<_>>();
+		Sample {
+			time: world.seconds().get(),
+			steps: self.steps,
+			counts,
+			births: self.births,
+			deaths: self.deaths,
+			fertilised: self.fertilised,
+			energy,
+			maturity,
+			feeder_throughput,
+			diversity: Diversity::of(&dnas),
+			species: world.species().iter().map(|s| (s.id(), s.size())).collect(),
+		}
+	}
+
+	fn write(&mut self, sample: &Sample) -> io::Result<()> {
+		match self.format {
+			StatsFormat::JsonLines => {
+				serde_json::to_writer(&mut self.out, sample)?;
+				writeln!(self.out)
+			}
+			StatsFormat::Csv => {
+				if !self.has_header {
+					self.has_header = true;
+					self.write_csv_header()?;
+				}
+				self.write_csv_row(sample)
+			}
+		}
+	}
+
+	fn write_csv_header(&mut self) -> io::Result<()> {
+		write!(self.out, "time,steps")?;
+		for t in AgentType::all() {
+			write!(self.out, ",{}_count", t.to_string().to_lowercase())?;
+		}
+		writeln!(
+			self.out,
+			",births,deaths,fertilised,energy_mean,energy_p10,energy_p50,energy_p90,\
+			 maturity_mean,maturity_p10,maturity_p50,maturity_p90,\
+			 distinct_genomes,mean_genome_distance,feeder_throughput,species_count,species_sizes"
+		)
+	}
+
+	fn write_csv_row(&mut self, sample: &Sample) -> io::Result<()> {
+		write!(self.out, "{},{}", sample.time, sample.steps)?;
+		for t in AgentType::all() {
+			write!(self.out, ",{}", sample.counts.get(&t.to_string()).cloned().unwrap_or(0))?;
+		}
+		let (e, m) = (&sample.energy, &sample.maturity);
+		writeln!(
+			self.out,
+			",{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+			sample.births,
+			sample.deaths,
+			sample.fertilised,
+			e.mean,
+			e.p10,
+			e.p50,
+			e.p90,
+			m.mean,
+			m.p10,
+			m.p50,
+			m.p90,
+			sample.diversity.distinct_genomes,
+			sample.diversity.mean_distance,
+			// a single field, the number of feeders can change during a run
+			sample.feeder_throughput.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(";"),
+			sample.species.len(),
+			sample.species.iter().map(|(id, size)| format!("{}:{}", id, size)).collect::<Vec<_>>().join(";")
+		)
+	}
+
+	pub fn flush(&mut self) -> io::Result<()> { self.out.flush() }
+}
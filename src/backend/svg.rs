@@ -0,0 +1,233 @@
+//! Vector rendering of agents and whole worlds as SVG, with no GPU involved
+use app::constants::*;
+use backend::obj;
+use backend::obj::{Id, Rgba, Transformable};
+use backend::world;
+use backend::world::agent;
+use backend::world::agent::Agent;
+use backend::world::boundary::Boundary;
+use backend::world::gen::{Dna, Genome};
+use backend::world::phen;
+use backend::world::segment::Segment;
+use cgmath;
+use core::clock::SimulationTimer;
+use core::geometry::{Position, Transform};
+use serialize::base64::{self, ToBase64};
+use std::fs;
+use std::io;
+use std::path;
+
+/// SVG user units per world unit
+const PIXELS_PER_UNIT: f32 = 20.;
+const SPECIMEN_MARGIN: f32 = 1.;
+const CAPTION_HEIGHT: f32 = 1.;
+const FEEDER_RADIUS: f32 = 1.;
+
+enum Outline {
+	Circle(Position, f32),
+	Polygon(Vec<Position>),
+}
+
+impl Outline {
+	/// The outline of a segment mesh, in world coordinates
+	fn of(segment: &Segment) -> Self {
+		let transform = segment.transform();
+		let scale = segment.growing_radius();
+		match segment.mesh.shape {
+			obj::Shape::Ball { .. } => Outline::Circle(transform.position, scale),
+			obj::Shape::Triangle { .. } =>
+				Outline::Polygon(Self::place(&segment.mesh.vertices[0..3], transform, scale)),
+			_ => Outline::Polygon(Self::place(&segment.mesh.vertices, transform, scale)),
+		}
+	}
+
+	fn place(vertices: &[Position], transform: &Transform, scale: f32) -> Vec<Position> {
+		let rotation = cgmath::Matrix2::from_angle(cgmath::Rad(transform.angle));
+		vertices.iter().map(|v| transform.position + rotation * (*v * scale)).collect()
+	}
+
+	fn bounds(&self) -> (Position, Position) {
+		match *self {
+			Outline::Circle(c, r) => (Position::new(c.x - r, c.y - r), Position::new(c.x + r, c.y + r)),
+			Outline::Polygon(ref points) => points.iter().fold(
+				(Position::new(f32::MAX, f32::MAX), Position::new(f32::MIN, f32::MIN)),
+				|(min, max), p| {
+					(Position::new(min.x.min(p.x), min.y.min(p.y)), Position::new(max.x.max(p.x), max.y.max(p.y)))
+				},
+			),
+		}
+	}
+
+	fn write<W>(&self, out: &mut W, fill: Rgba) -> io::Result<()>
+	where W: io::Write {
+		let style =
+			format!(r#"fill="{}" fill-opacity="{:.2}" stroke="{}""#, hex(fill), fill[3].min(1.), hex(darker(fill)));
+		match *self {
+			Outline::Circle(c, r) => writeln!(out, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, c.x, c.y, r, style),
+			Outline::Polygon(ref points) => {
+				let points = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect::<Vec<_>>().join(" ");
+				writeln!(out, r#"<polygon points="{}" {}/>"#, points, style)
+			}
+		}
+	}
+}
+
+fn hex(color: Rgba) -> String {
+	let channel = |c: f32| (c.clamp(0., 1.) * 255.).round() as u8;
+	format!("#{:02x}{:02x}{:02x}", channel(color[0]), channel(color[1]), channel(color[2]))
+}
+
+fn darker(color: Rgba) -> Rgba { [color[0] * 0.5, color[1] * 0.5, color[2] * 0.5, color[3]] }
+
+fn agent_bounds(agent: &Agent) -> (Position, Position) {
+	agent.segments().iter().map(|s| Outline::of(s).bounds()).fold(
+		(Position::new(f32::MAX, f32::MAX), Position::new(f32::MIN, f32::MIN)),
+		|(min, max), (lo, hi)| {
+			(Position::new(min.x.min(lo.x), min.y.min(lo.y)), Position::new(max.x.max(hi.x), max.y.max(hi.y)))
+		},
+	)
+}
+
+/// Segments are drawn in their livery colour, rather than tinted by their
+/// charge as on screen
+fn write_agent<W>(out: &mut W, agent: &Agent) -> io::Result<()>
+where W: io::Write {
+	writeln!(out, r#"<g id="agent-{}">"#, agent.id())?;
+	for segment in agent.segments() {
+		Outline::of(segment).write(out, segment.livery.albedo)?;
+	}
+	writeln!(out, "</g>")
+}
+
+/// The y axis of the world points up, that of SVG down
+fn write_header<W>(out: &mut W, min: Position, max: Position) -> io::Result<()>
+where W: io::Write {
+	let (width, height) = (max.x - min.x, max.y - min.y);
+	writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+	writeln!(
+		out,
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+		width * PIXELS_PER_UNIT,
+		height * PIXELS_PER_UNIT,
+		min.x,
+		-max.y,
+		width,
+		height
+	)?;
+	writeln!(
+		out,
+		r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+		min.x,
+		-max.y,
+		width,
+		height,
+		hex(BACKGROUNDS[0])
+	)
+}
+
+/// Minions developed from the given genomes, fully grown and facing up
+pub fn specimens(dnas: &[Dna]) -> Vec<Agent> {
+	let timer = SimulationTimer::new();
+	let phenotype = phen::phenotype_of(agent::AgentType::Minion);
+	dnas.iter()
+		.filter(|dna| !dna.is_empty())
+		.enumerate()
+		.map(|(i, dna)| {
+			let initial_state = agent::InitialState { maturity: Some(1.), ..Default::default() };
+			phenotype.develop(&mut Genome::copy_from(dna), i as Id, initial_state, &timer)
+		})
+		.collect()
+}
+
+/// A grid of specimens, each with its DNA as a caption
+pub fn write_specimen_sheet<W>(out: &mut W, specimens: &[Agent]) -> io::Result<()>
+where W: io::Write {
+	let bounds = specimens.iter().map(agent_bounds).collect::<Vec<_>>();
+	let cell = bounds.iter().map(|(min, max)| (max.x - min.x).max(max.y - min.y)).fold(1., f32::max) + SPECIMEN_MARGIN;
+	let columns = (specimens.len() as f32).sqrt().ceil().max(1.) as usize;
+	let rows = specimens.len().div_ceil(columns);
+	let (width, height) = (columns as f32 * cell, rows.max(1) as f32 * (cell + CAPTION_HEIGHT));
+	write_header(out, Position::new(0., -height), Position::new(width, 0.))?;
+	for (i, (specimen, (min, max))) in specimens.iter().zip(bounds.iter()).enumerate() {
+		let left = (i % columns) as f32 * cell;
+		let top = (i / columns) as f32 * (cell + CAPTION_HEIGHT);
+		let centre = Position::new((min.x + max.x) / 2., (min.y + max.y) / 2.);
+		writeln!(
+			out,
+			r#"<g transform="translate({} {}) scale(1 -1) translate({} {})">"#,
+			left + cell / 2.,
+			top + cell / 2.,
+			-centre.x,
+			-centre.y
+		)?;
+		write_agent(out, specimen)?;
+		writeln!(out, "</g>")?;
+		writeln!(
+			out,
+			r#"<text x="{}" y="{}" font-family="monospace" font-size="{}" fill="{}" text-anchor="middle">{}</text>"#,
+			left + cell / 2.,
+			top + cell + CAPTION_HEIGHT / 2.,
+			CAPTION_HEIGHT * 0.4,
+			hex(COLOR_WHITE),
+			specimen.dna().to_base64(base64::STANDARD)
+		)?;
+	}
+	writeln!(out, "</svg>")
+}
+
+/// The extent, feeders and agents of a world
+pub fn write_world<W>(out: &mut W, world: &world::World) -> io::Result<()>
+where W: io::Write {
+	let extent = &world.extent;
+	write_header(out, extent.min, extent.max)?;
+	writeln!(out, r#"<g transform="scale(1 -1)">"#)?;
+	writeln!(
+		out,
+		r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="0.2"/>"#,
+		extent.min.x,
+		extent.min.y,
+		extent.max.x - extent.min.x,
+		extent.max.y - extent.min.y,
+		hex(COLOR_WHITE)
+	)?;
+	if world.boundary == Boundary::Circle {
+		let centre = Boundary::centre(extent);
+		writeln!(
+			out,
+			r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="0.2"/>"#,
+			centre.x,
+			centre.y,
+			Boundary::radius(extent),
+			hex(COLOR_WHITE)
+		)?;
+	}
+	for feeder in world.feeders() {
+		let p = feeder.transform().position;
+		writeln!(
+			out,
+			r#"<circle cx="{}" cy="{}" r="{}" fill="{}" fill-opacity="{:.2}"/>"#,
+			p.x,
+			p.y,
+			FEEDER_RADIUS,
+			hex(COLOR_WHITE),
+			0.2 + 0.8 * feeder.intensity().min(1.)
+		)?;
+	}
+	for agent_type in agent::AgentType::all() {
+		writeln!(out, r#"<g class="{}">"#, agent_type)?;
+		for agent in world.agents(*agent_type).values() {
+			write_agent(out, agent)?;
+		}
+		writeln!(out, "</g>")?;
+	}
+	writeln!(out, "</g>")?;
+	writeln!(out, "</svg>")
+}
+
+pub fn save_specimen_sheet(file_path: &path::Path, specimens: &[Agent]) -> io::Result<()> {
+	write_specimen_sheet(&mut io::BufWriter::new(fs::File::create(file_path)?), specimens)
+}
+
+pub fn save_world(file_path: &path::Path, world: &world::World) -> io::Result<()> {
+	write_world(&mut io::BufWriter::new(fs::File::create(file_path)?), world)
+}
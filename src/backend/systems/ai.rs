@@ -7,19 +7,24 @@ use backend::world;
 use backend::world::agent;
 use backend::world::agent::Personality;
 use backend::world::agent::TypedAgent;
+use backend::world::boundary::Boundary;
 use backend::world::segment;
 use backend::world::segment::Intent;
 use cgmath::*;
 use core::geometry::Position;
+use core::geometry::Rect;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::f32::consts;
 
-type IdPositionMap = HashMap<obj::Id, Position>;
+type IdPositionMap = BTreeMap<obj::Id, Position>;
 
 pub struct AiSystem {
 	beacons: Box<[Position]>,
 	targets: IdPositionMap,
+	collision_cost: f32,
+	extent: Rect,
+	boundary: Boundary,
 }
 
 impl System for AiSystem {
@@ -29,29 +34,56 @@ impl System for AiSystem {
 	}
 
 	fn import(&mut self, world: &world::World) {
+		self.collision_cost = world.config().collision_base_cost;
+		self.extent = world.extent;
+		self.boundary = world.boundary;
 		self.beacons = world.feeders().iter().map(|e| e.transform().position).collect::<Vec<_>>().into_boxed_slice();
 		self.targets = world
 			.agents(agent::AgentType::Resource)
 			.iter()
 			.filter(|&(_, ref v)| v.state.is_active())
 			.map(|(_, v)| (v.id(), v.transform().position))
-			.collect::<HashMap<_, _>>();
+			.collect::<BTreeMap<_, _>>();
 	}
 
 	fn export(&self, world: &mut world::World, _outbox: &dyn Outbox) {
-		Self::update_minions(&self.targets, &self.beacons, &mut world.agents_mut(agent::AgentType::Minion));
+		Self::update_minions(
+			&self.targets,
+			&self.beacons,
+			self.collision_cost,
+			&self.extent,
+			self.boundary,
+			world.agents_mut(agent::AgentType::Minion),
+		);
 	}
 }
 
 impl Default for AiSystem {
-	fn default() -> Self { AiSystem { beacons: Box::new([]), targets: HashMap::new() } }
+	fn default() -> Self {
+		AiSystem {
+			beacons: Box::new([]),
+			targets: BTreeMap::new(),
+			collision_cost: COLLISION_BASE_COST,
+			extent: Rect::default(),
+			boundary: Boundary::default(),
+		}
+	}
 }
 
 impl AiSystem {
-	fn update_minions(targets: &IdPositionMap, beacons: &[Position], minions: &mut agent::AgentMap) {
-		fn nearest_beacon<'a>(beacons: &'a [Position], p: &'a Position) -> &'a Position {
-			beacons.iter().fold1(|n, b| if (p - n).magnitude2() < (p - b).magnitude2() { n } else { b }).unwrap_or(p)
-		}
+	fn update_minions(
+		targets: &IdPositionMap,
+		beacons: &[Position],
+		collision_cost: f32,
+		extent: &Rect,
+		boundary: Boundary,
+		minions: &mut agent::AgentMap,
+	) {
+		// distances go across the edges of a wrapping world
+		let distance2 = |a: Position, b: Position| boundary.delta(extent, a, b).magnitude2();
+		let nearest_beacon = |p: Position| -> Position {
+			beacons.iter().cloned().fold1(|n, b| if distance2(p, n) < distance2(p, b) { n } else { b }).unwrap_or(p)
+		};
 
 		for (_, agent) in minions.iter_mut() {
 			let brain = agent.brain().clone();
@@ -66,19 +98,19 @@ impl AiSystem {
 				let new_target: Option<(obj::Id, Position)> = match current_target {
 					None => targets
 						.iter()
-						.find(|&(_, &p)| (p - p0).magnitude() < radar_range)
+						.find(|&(_, &p)| distance2(p0, p) < radar_range * radar_range)
 						.map(|(&id, &position)| (id, position)),
 					Some(id) => targets.get(&id).map(|&position| (id, position)),
 				};
 				// and failing that again, we target
 				match new_target {
-					None => agent.state.retarget(None, *nearest_beacon(beacons, &current_target_position)),
+					None => agent.state.retarget(None, nearest_beacon(current_target_position)),
 					Some((id, position)) => agent.state.retarget(Some(id), position),
 				};
 				// find where our target is in the world
 				let target_position = agent.state.target_position();
 				// and transform the world position into the head's frame
-				let t0 = target_position - sensor.transform.position;
+				let t0 = boundary.delta(extent, sensor.transform.position, target_position);
 				let t = t0.normalize_to(t0.magnitude().min(radar_range));
 				// direction in which the head is pointing, normalized
 				let s = Matrix2::from_angle(Rad(sensor.transform.angle)) * (-Position::unit_y());
@@ -103,7 +135,7 @@ impl AiSystem {
 								agent::AgentType::Resource => Intent::Idle,
 								_ => {
 									let fear: f32 = brain.fear();
-									touch_accumulator += COLLISION_BASE_COST / segment.state.maturity();
+									touch_accumulator += collision_cost / segment.state.maturity();
 									Intent::RunAway(f * fear)
 								}
 							}
@@ -1,5 +1,5 @@
 use super::*;
-use app::constants::*;
+use backend::config::SimulationConfig;
 use backend::messagebus::Outbox;
 use backend::obj;
 use backend::obj::Identified;
@@ -7,13 +7,14 @@ use backend::obj::Transformable;
 use backend::world;
 use backend::world::agent;
 use backend::world::alert;
+use backend::world::boundary::Boundary;
 use backend::world::gen;
+use backend::world::lineage::Lineage;
 use backend::world::particle;
 use backend::world::segment;
 use backend::world::AgentState;
 use core::clock::SimulationTimer;
 use core::geometry;
-use rand;
 use serialize::base64::{self, ToBase64};
 use std::collections::HashMap;
 
@@ -26,6 +27,7 @@ pub struct AlifeSystem {
 	source: Box<[world::Feeder]>,
 	eaten: StateMap,
 	touched: GeneMap,
+	config: SimulationConfig,
 }
 
 impl System for AlifeSystem {
@@ -36,7 +38,11 @@ impl System for AlifeSystem {
 	}
 
 	fn import(&mut self, world: &world::World) {
+		// lifecycles are measured against the world clock, which is restored
+		// with snapshots
+		self.simulation_timer = SimulationTimer::from(world.seconds());
 		self.source = world.feeders().to_vec().into_boxed_slice();
+		self.config = world.config().clone();
 		self.eaten = Self::find_eaten_resources(
 			&world.agents(agent::AgentType::Minion),
 			&world.agents(agent::AgentType::Resource),
@@ -61,7 +67,9 @@ impl System for AlifeSystem {
 		let MinionEndState(spores, corpses) = Self::update_minions(
 			outbox,
 			self.dt,
+			&self.config,
 			world.extent,
+			world.boundary,
 			&mut world.agents_mut(agent::AgentType::Minion),
 			&self.eaten,
 		);
@@ -73,14 +81,14 @@ impl System for AlifeSystem {
 			&self.touched,
 		);
 
-		for (transform, dna) in &*spores {
+		for (transform, dna, parent) in &*spores {
 			outbox.post(alert::Alert::NewSpore.into());
-			world.new_spore(outbox, transform.clone(), dna);
+			world.new_spore(outbox, transform.clone(), dna, *parent);
 		}
 
-		for (transform, dna) in &*hatch {
+		for (transform, dna, foreign_dna, lineage) in &*hatch {
 			outbox.post(alert::Alert::NewMinion.into());
-			world.hatch_spore(outbox, transform.clone(), dna);
+			world.hatch_spore(outbox, transform.clone(), dna, foreign_dna, lineage);
 		}
 
 		for (transforms, dna) in &*corpses {
@@ -91,7 +99,7 @@ impl System for AlifeSystem {
 		}
 
 		for _ in 0..fertilised {
-			outbox.post(alert::Alert::DieMinion.into());
+			outbox.post(alert::Alert::Fertilised.into());
 		}
 	}
 }
@@ -104,13 +112,17 @@ impl Default for AlifeSystem {
 			source: Box::new([]),
 			eaten: StateMap::new(),
 			touched: GeneMap::new(),
+			config: SimulationConfig::default(),
 		}
 	}
 }
 
-struct MinionEndState(Box<[(geometry::Transform, gen::Dna)]>, Box<[(Box<[geometry::Transform]>, gen::Dna)]>);
+struct MinionEndState(Box<[(geometry::Transform, gen::Dna, obj::Id)]>, Box<[(Box<[geometry::Transform]>, gen::Dna)]>);
+
+/// Position, DNA, foreign DNA if fertilised, and lineage of a minion to hatch
+type Hatch = (geometry::Transform, gen::Dna, Option<gen::Dna>, Lineage);
 
-struct SporeEndState(Box<[(geometry::Transform, gen::Dna)]>, usize);
+struct SporeEndState(Box<[Hatch]>, usize);
 
 impl AlifeSystem {
 	fn find_eaten_resources(minions: &agent::AgentMap, resources: &agent::AgentMap) -> StateMap {
@@ -146,7 +158,9 @@ impl AlifeSystem {
 	fn update_minions(
 		outbox: &dyn Outbox,
 		dt: Seconds,
+		config: &SimulationConfig,
 		extent: geometry::Rect,
+		boundary: Boundary,
 		minions: &mut agent::AgentMap,
 		eaten: &StateMap,
 	) -> MinionEndState {
@@ -162,7 +176,7 @@ impl AlifeSystem {
 				let transform = segment.transform().clone();
 				if maturity < 1. {
 					// just grow a bit
-					let r = GROWTH_COST_RATIO;
+					let r = config.growth_cost_ratio;
 					if agent.state.consume_ratio(1. - r, r) {
 						let growth = 1. + r;
 						agent.state.grow_by(growth);
@@ -175,13 +189,13 @@ impl AlifeSystem {
 							segment.transform.position = zero + (segment.transform.position - zero) * growth;
 						}
 					}
-				} else if agent.state.consume_ratio(SPAWN_COST_THRESHOLD, SPAWN_COST_RATIO) {
-					spawns.push((agent.last_segment().transform().clone(), agent.dna().clone()));
+				} else if agent.state.consume_ratio(config.spawn_cost_threshold, config.spawn_cost_ratio) {
+					spawns.push((agent.last_segment().transform().clone(), agent.dna().clone(), agent.id()));
 				}
 
 				for segment in agent.segments.iter_mut() {
-					let p = segment.transform().position;
-					if p.x < extent.min.x || p.x > extent.max.x || p.y < extent.min.y || p.y > extent.max.y {
+					// the other boundaries keep the minions inside, see PhysicsSystem
+					if boundary == Boundary::Kill && !boundary.contains(&extent, segment.transform().position) {
 						agent.state.die();
 					}
 					if segment.flags.contains(segment::Flags::MOUTH) {
@@ -225,13 +239,6 @@ impl AlifeSystem {
 		}
 	}
 
-	fn crossover(dna: &gen::Dna, foreign_dna: &Option<gen::Dna>) -> gen::Dna {
-		match *foreign_dna {
-			Some(ref foreign) => gen::Genome::copy_from(&foreign).crossover(&mut rand::thread_rng(), dna).dna_cloned(),
-			None => dna.clone(),
-		}
-	}
-
 	fn update_spores(
 		dt: Seconds,
 		timer: &SimulationTimer,
@@ -243,7 +250,12 @@ impl AlifeSystem {
 		for (spore_id, spore) in spores.iter_mut() {
 			if spore.state.lifecycle().is_expired(timer) {
 				spore.state.die();
-				spawns.push((spore.transform().clone(), Self::crossover(spore.dna(), spore.state.foreign_dna())))
+				spawns.push((
+					spore.transform().clone(),
+					spore.dna().clone(),
+					spore.state.foreign_dna().clone(),
+					spore.state.lineage().clone(),
+				))
 			} else if spore.state.is_active() {
 				for segment in spore.segments.iter_mut() {
 					if let Some(key) = segment.state.last_touched {
@@ -255,7 +267,7 @@ impl AlifeSystem {
 								touched_dna.to_base64(base64::STANDARD)
 							);
 							fertilise_count += 1;
-							spore.state.fertilise(touched_dna);
+							spore.state.fertilise(touched_dna, key.id());
 						}
 					}
 				}
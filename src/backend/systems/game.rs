@@ -5,12 +5,13 @@ use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
 use backend::obj::Transformable;
 use backend::world;
 use backend::world::agent;
+use backend::world::environment::Conditions;
 use cgmath::InnerSpace;
 use core::clock::*;
 use core::geometry::Transform;
 use core::geometry::*;
 use core::math::{exponential_filter, ExponentialFilter};
-use rand;
+use core::rng::XorShiftRng;
 use rand::Rng;
 use std::f32::consts;
 
@@ -24,18 +25,24 @@ pub struct PlayerState {
 }
 
 pub struct GameSystem {
-	timer: SimulationTimer,
 	dt: Seconds,
 	playerstate: PlayerState,
 	feeders: Vec<Feeder>,
+	feeders_revision: usize,
 	inbox: Option<Inbox>,
+	rng: XorShiftRng,
 }
 
 struct Feeder {
 	angle: Angle,
 	position: Position,
+	/// Runs faster when food is abundant, and slower when it is scarce
+	clock: SimulationTimer,
 	hourglass: Hourglass,
 	light_intensity: ExponentialFilter<f32>,
+	power: f32,
+	orbit: bool,
+	conditions: Conditions,
 	to_spawn: usize,
 	spawned: usize,
 	spin: Spin,
@@ -45,13 +52,18 @@ struct Feeder {
 
 impl Feeder //where
 {
-	fn new<T>(position: Position, rate: Seconds, timer: &T) -> Self
-	where T: Timer {
+	/// Resumes the state of a world feeder
+	fn new(source: &world::Feeder, period: Seconds, conditions: Conditions, intensity_decay: f32) -> Self {
+		let clock = SimulationTimer::new();
 		Feeder {
-			angle: 0.,
-			position,
-			light_intensity: exponential_filter(0., 0., EMITTER_INTENSITY_DECAY),
-			hourglass: Hourglass::new(rate, timer),
+			angle: source.transform().angle,
+			position: source.transform().position,
+			light_intensity: exponential_filter(0., source.intensity(), intensity_decay),
+			hourglass: Hourglass::resume(period, source.next_emission(), &clock),
+			clock,
+			power: source.power(),
+			orbit: source.is_orbiting(),
+			conditions,
 			to_spawn: 0,
 			spawned: 0,
 			spin: consts::PI * 0.25,
@@ -83,36 +95,55 @@ impl System for GameSystem {
 			}
 		}
 
+		self.rng = world.fork_rng();
+
 		let source = world.feeders();
-		// Add missing emitters - deletion not supported
-		for s in &source[self.feeders.len()..] {
-			//			let s = &source[i];
-			self.feeders.push(Feeder::new(s.transform().position, s.rate(), &self.timer));
+		// when feeders are added or removed, all of them resume from the state
+		// exported at the end of the last step
+		if self.feeders_revision != world.feeders_revision() || self.feeders.len() != source.len() {
+			let decay = world.config().emitter_intensity_decay;
+			self.feeders = source
+				.iter()
+				.enumerate()
+				.map(|(i, s)| Feeder::new(s, world.feeder_period(i), world.feeder_conditions(i), decay))
+				.collect();
+			self.feeders_revision = world.feeders_revision();
 		}
 		for (i, d) in self.feeders.iter_mut().enumerate() {
 			d.position = source[i].transform().position;
+			d.power = source[i].power();
+			d.orbit = source[i].is_orbiting();
+			d.conditions = world.feeder_conditions(i);
+			// the rate may have been changed by a new configuration
+			let rate = world.feeder_period(i);
+			if d.hourglass.capacity() != rate {
+				let left = seconds(source[i].next_emission().get().min(rate.get()));
+				d.hourglass = Hourglass::resume(rate, left, &d.clock);
+			}
 		}
 	}
 
 	fn update(&mut self, _: &dyn world::AgentState, dt: Seconds) {
-		let rng = &mut rand::thread_rng();
+		let rng = &mut self.rng;
 		self.dt = dt;
 
-		self.timer.tick(dt);
 		for e in &mut self.feeders {
 			e.spawned = e.to_spawn;
 		}
 		for e in &mut self.feeders {
-			if e.hourglass.is_expired(&self.timer) {
-				e.hourglass.flip(&self.timer);
+			e.clock.tick(dt.times(e.conditions.abundance));
+			if e.hourglass.is_expired(&e.clock) {
+				e.hourglass.flip(&e.clock);
 				e.hourglass.delay(seconds(rng.next_f32() * EMITTER_SPREAD_JITTER));
-				e.light_intensity.force_to(1.0);
+				e.light_intensity.force_to(e.power * e.conditions.light);
 				e.to_spawn += 1;
 			}
 			e.light_intensity.update(dt.get() as f32);
-			let tangent = Position::new(-e.position.y, e.position.x).normalize();
 			e.angle += dt * e.spin;
-			e.position += tangent * (dt * rng.next_f32());
+			if e.orbit {
+				let tangent = Position::new(-e.position.y, e.position.x).normalize();
+				e.position += tangent * (dt * rng.next_f32());
+			}
 		}
 		// Byzantine way of processing trigger presses without trigger releases
 		// I should think of something less convoluted
@@ -135,9 +166,10 @@ impl System for GameSystem {
 			for _ in e.spawned..e.to_spawn {
 				let r = e.angle;
 
-				world.new_resource(
+				world.new_resource_with_charge(
 					Transform::new(e.position, r),
 					Motion::new(Velocity::new(r.cos(), r.sin()) * e.emitted_velocity, e.emitted_spin),
+					DEFAULT_RESOURCE_CHARGE * e.power * e.conditions.charge,
 				);
 			}
 		}
@@ -145,6 +177,8 @@ impl System for GameSystem {
 		for (src, dest) in self.feeders.iter().zip(world.feeders_mut().iter_mut()) {
 			dest.transform_to(Transform::new(src.position, src.angle));
 			dest.set_intensity(src.light_intensity.get());
+			dest.count_emitted(src.to_spawn - src.spawned);
+			dest.set_next_emission(src.hourglass.left(&src.clock));
 		}
 
 		if self.playerstate.bullet_ready {
@@ -180,11 +214,12 @@ impl System for GameSystem {
 impl Default for GameSystem {
 	fn default() -> Self {
 		GameSystem {
-			timer: SimulationTimer::new(),
 			dt: seconds(0.),
 			playerstate: PlayerState::default(),
 			feeders: Vec::new(),
+			feeders_revision: 0,
 			inbox: None,
+			rng: XorShiftRng::from_seed(0),
 		}
 	}
 }
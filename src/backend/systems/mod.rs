@@ -4,6 +4,7 @@ pub mod animation;
 pub mod game;
 pub mod particle;
 pub mod physics;
+pub mod species;
 
 pub use self::ai::AiSystem;
 pub use self::alife::AlifeSystem;
@@ -11,11 +12,15 @@ pub use self::animation::AnimationSystem;
 pub use self::game::GameSystem;
 pub use self::particle::ParticleSystem;
 pub use self::physics::PhysicsSystem;
+pub use self::species::SpeciesSystem;
 
 use backend::messagebus::{Outbox, PubSub};
 use backend::world;
 
 use core::clock::Seconds;
+use rayon::prelude::*;
+use std::sync::Arc;
+use std::sync::RwLock;
 
 pub trait System {
 	fn attach(&mut self, _: &mut PubSub) {}
@@ -34,3 +39,151 @@ pub trait System {
 
 	fn apply(&self, world: &mut world::World, outbox: &dyn Outbox) { self.export(world, outbox) }
 }
+
+#[derive(Default)]
+struct SendSystem<T>
+where T: System {
+	ptr: Arc<RwLock<T>>,
+}
+
+impl<T> SendSystem<T>
+where T: System
+{
+	fn boxed(ptr: Arc<RwLock<T>>) -> Box<Self> { Box::new(SendSystem { ptr }) }
+}
+
+impl<T> System for SendSystem<T>
+where T: System
+{
+	fn attach(&mut self, bus: &mut PubSub) { self.ptr.write().unwrap().attach(bus) }
+	fn init(&mut self, world: &world::World) { self.ptr.write().unwrap().init(world) }
+	fn clear(&mut self) { self.ptr.write().unwrap().clear() }
+	fn register(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().register(agent) }
+	fn unregister(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().unregister(agent) }
+
+	fn step(&mut self, world: &world::World, dt: Seconds) { self.ptr.write().unwrap().step(world, dt) }
+	fn apply(&self, world: &mut world::World, outbox: &dyn Outbox) { self.ptr.read().unwrap().apply(world, outbox) }
+}
+
+// unsafe?
+unsafe impl<T> Send for SendSystem<T> where T: System {}
+
+/// Interactive mode runs all the systems, batch mode skips those which only
+/// matter for rendering
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum SystemMode {
+	#[default]
+	Interactive,
+	Batch,
+}
+
+/// The full set of systems acting on a world, stepped in parallel
+#[derive(Default)]
+pub struct Systems {
+	mode: SystemMode,
+	physics: Arc<RwLock<PhysicsSystem>>,
+	animation: Arc<RwLock<AnimationSystem>>,
+	game: Arc<RwLock<GameSystem>>,
+	ai: Arc<RwLock<AiSystem>>,
+	alife: Arc<RwLock<AlifeSystem>>,
+	particle: Arc<RwLock<ParticleSystem>>,
+	species: Arc<RwLock<SpeciesSystem>>,
+}
+
+impl Systems {
+	pub fn new(mode: SystemMode) -> Self { Systems { mode, ..Systems::default() } }
+
+	pub fn mode(&self) -> SystemMode { self.mode }
+
+	pub fn set_mode(&mut self, mode: SystemMode) { self.mode = mode; }
+
+	fn systems(&mut self) -> Vec<Box<dyn System + Send>> {
+		match self.mode {
+			SystemMode::Interactive => vec![
+				SendSystem::boxed(self.physics.clone()),
+				SendSystem::boxed(self.animation.clone()),
+				SendSystem::boxed(self.particle.clone()),
+				SendSystem::boxed(self.game.clone()),
+				SendSystem::boxed(self.ai.clone()),
+				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.species.clone()),
+			],
+			SystemMode::Batch => vec![
+				SendSystem::boxed(self.physics.clone()),
+				SendSystem::boxed(self.game.clone()),
+				SendSystem::boxed(self.ai.clone()),
+				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.species.clone()),
+			],
+		}
+	}
+
+	pub fn unregister(&mut self, agents: &[world::agent::Agent]) {
+		if !agents.is_empty() {
+			self.systems().par_iter_mut().for_each(|system| {
+				for agent in agents {
+					system.unregister(agent)
+				}
+			})
+		}
+	}
+
+	pub fn register(&mut self, agents: &[world::agent::Agent]) {
+		if !agents.is_empty() {
+			self.systems().par_iter_mut().for_each(|system| {
+				for agent in agents {
+					system.register(agent)
+				}
+			})
+		}
+	}
+
+	pub fn init(&mut self, world: &world::World) {
+		for system in &mut self.systems() {
+			system.init(world);
+		}
+	}
+
+	pub fn clear(&mut self) {
+		for system in &mut self.systems() {
+			system.clear();
+		}
+	}
+
+	pub fn attach(&mut self, bus: &mut PubSub) {
+		for system in &mut self.systems() {
+			system.attach(bus);
+		}
+	}
+
+	/// Registers all the agents added to the world since the last call
+	pub fn register_all(&mut self, world: &mut world::World) {
+		// registered() drains the list, so this can be called only once per frame
+		let found: Vec<world::agent::Agent> =
+			world.registered().iter().filter_map(|id| world.agent(*id)).cloned().collect();
+		self.register(&found[..]);
+	}
+
+	/// Advances the world by one step of length dt
+	pub fn simulate(&mut self, world: &mut world::World, outbox: &dyn Outbox, dt: Seconds) {
+		world.cleanup_before();
+		self.unregister(&world.sweep());
+		self.for_each_par_write(world, &|s, world| s.step(world, dt));
+		self.for_each_read(world, outbox, &|s, world, outbox| s.apply(world, outbox));
+		self.register_all(world);
+		world.tick(dt);
+	}
+
+	fn for_each_read(
+		&mut self,
+		world: &mut world::World,
+		outbox: &dyn Outbox,
+		apply: &(dyn Fn(&mut dyn System, &mut world::World, &dyn Outbox) + Sync),
+	) {
+		self.systems().iter_mut().for_each(|r| apply(&mut (**r), world, outbox))
+	}
+
+	fn for_each_par_write(&mut self, world: &world::World, apply: &(dyn Fn(&mut dyn System, &world::World) + Sync)) {
+		self.systems().par_iter_mut().for_each(|r| apply(&mut (**r), world))
+	}
+}
@@ -9,13 +9,13 @@ use cgmath::InnerSpace;
 use core::clock::{seconds, Seconds, SimulationTimer, TimerStopwatch};
 use core::color::Rgba;
 use core::geometry::{Acceleration, Motion, Position, Transform, Velocity};
+use core::rng::XorShiftRng;
 use num;
 use num::NumCast;
 use num::Zero;
-use rand;
 use rand::Rng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::convert;
 use std::f32::consts;
@@ -88,9 +88,17 @@ struct Particle {
 }
 
 trait Emitter {
-	fn emit(&mut self, dt: Seconds, id_counter: &mut usize, destination: &mut HashMap<obj::Id, ParticleBatch>) -> bool;
+	fn emit(
+		&mut self,
+		dt: Seconds,
+		rng: &mut XorShiftRng,
+		id_counter: &mut usize,
+		destination: &mut BTreeMap<obj::Id, ParticleBatch>,
+	) -> bool;
 	fn attached_to(&self) -> EmitterAttachment { EmitterAttachment::None }
 	fn update_transform(&mut self, _transform: Transform, _motion: Motion) {}
+	/// The current state of the emitter, if it can be restored
+	fn snapshot(&self) -> Option<world::particle::Emitter> { None }
 }
 
 #[derive(Clone)]
@@ -118,6 +126,7 @@ struct SimpleEmitter {
 	lifespan: Seconds,
 	jitter: f32,
 	active: bool,
+	source: Option<world::particle::Emitter>,
 }
 
 #[allow(unused)]
@@ -147,6 +156,7 @@ impl Default for SimpleEmitter {
 			jitter: 1.,
 			lifespan: seconds(10.),
 			active: true,
+			source: None,
 		}
 	}
 }
@@ -221,11 +231,20 @@ impl SimpleEmitter {
 
 	pub fn with_cluster_size(self, cluster_size: u8) -> Self { SimpleEmitter { cluster_size, ..self } }
 	pub fn with_cluster_spread(self, cluster_spread: f32) -> Self { SimpleEmitter { cluster_spread, ..self } }
+
+	pub fn with_source(self, source: world::particle::Emitter) -> Self {
+		SimpleEmitter { source: Some(source), ..self }
+	}
 }
 
 impl Emitter for SimpleEmitter {
-	fn emit(&mut self, dt: Seconds, id_counter: &mut usize, destination: &mut HashMap<obj::Id, ParticleBatch>) -> bool {
-		let mut rng = rand::thread_rng();
+	fn emit(
+		&mut self,
+		dt: Seconds,
+		rng: &mut XorShiftRng,
+		id_counter: &mut usize,
+		destination: &mut BTreeMap<obj::Id, ParticleBatch>,
+	) -> bool {
 		let jitter_value = self.jitter;
 		let mut jitter = move |w| (rng.next_f32() * 2. * w - w) * jitter_value + 1.;
 		if self.active {
@@ -288,17 +307,26 @@ impl Emitter for SimpleEmitter {
 		self.transform = transform;
 		self.frame_motion = motion;
 	}
+
+	fn snapshot(&self) -> Option<world::particle::Emitter> {
+		self.source.as_ref().map(|source| world::particle::Emitter {
+			transform: self.transform.clone(),
+			ttl: self.ttl,
+			..source.clone()
+		})
+	}
 }
 
 #[allow(unused)]
 pub struct ParticleSystem {
 	id_counter: usize,
 	inbox: Option<Inbox>,
-	particles: HashMap<obj::Id, ParticleBatch>,
-	emitters: HashMap<obj::Id, Box<dyn Emitter>>,
+	particles: BTreeMap<obj::Id, ParticleBatch>,
+	emitters: BTreeMap<obj::Id, Box<dyn Emitter>>,
 	dt: Seconds,
 	simulation_timer: SimulationTimer,
 	simulation_clock: TimerStopwatch,
+	rng: XorShiftRng,
 }
 
 impl System for ParticleSystem {
@@ -306,6 +334,42 @@ impl System for ParticleSystem {
 		self.inbox = Some(bus.subscribe(Box::new(|m| matches!(*m, Message::NewEmitter(_)))));
 	}
 
+	/// Picks up the emitters and particles of a restored world
+	fn init(&mut self, world: &world::World) {
+		for source in world.emitters() {
+			self.add_emitter(source.clone());
+		}
+		for particle in world.particles() {
+			let id = self.next_id();
+			// fade rates are not kept, the faders hold their current values
+			let mut faders = [None; MAX_FADER];
+			for (src, dest) in particle.faders().iter().zip(faders.iter_mut()) {
+				*dest = Some(Fader::flat(*src));
+			}
+			let trail = particle.trail().iter().cloned().collect::<VecDeque<_>>();
+			self.particles.insert(id, ParticleBatch {
+				id,
+				tag: particle.tag(),
+				// the same for all the emitters
+				lifespan: seconds(3.0),
+				age: particle.age(),
+				color: particle.colors(),
+				effect: particle.effects(),
+				dampening: 0.,
+				friction: SimpleEmitter::default().friction,
+				faders,
+				trail_length: trail.len() as u8,
+				particles: vec![Particle {
+					transform: particle.transform(),
+					motion: Motion::new(particle.velocity(), 0.),
+					acceleration: Acceleration::zero(),
+					trail,
+				}]
+				.into_boxed_slice(),
+			});
+		}
+	}
+
 	fn clear(&mut self) {
 		self.particles.clear();
 		self.emitters.clear();
@@ -321,47 +385,11 @@ impl System for ParticleSystem {
 			}
 		}
 		for source in emitters {
-			let emitter = match source.style {
-				EmitterStyle::Explosion { cluster_size, color } => SimpleEmitter::new(self.next_id())
-					.with_transform(source.transform.clone())
-					.with_motion(Motion::new(10. * Velocity::unit_x(), 0.))
-					.with_color(color, COLOR_TRANSPARENT)
-					.with_ttl(Some(seconds(0.5)))
-					.with_cluster_size(cluster_size)
-					.with_cluster_spread(0.5)
-					.with_2_faders(Fader::new(0.0, 2.0, 1.0), Fader::new(1.0, 0.7, 0.1))
-					.with_lifespan(seconds(3.0)),
-				EmitterStyle::Ping { color } => SimpleEmitter::new(self.next_id())
-					.with_attached_to(source.attached_to)
-					.with_transform(source.transform.clone())
-					.with_color(color, COLOR_TRANSPARENT)
-					.with_ttl(Some(seconds(0.33)))
-					.with_4_faders(
-						Fader::new(0.0, 4.0, 1.0),
-						Fader::new(0.5, 0.7, 5.0),
-						Fader::flat(1.),
-						Fader::new(10., 1., 5.),
-					)
-					.with_cluster_size(1)
-					.with_lifespan(seconds(3.0)),
-				EmitterStyle::Sparkle { cluster_size, color } => SimpleEmitter::new(self.next_id())
-					.with_jitter(0.)
-					.with_transform(source.transform.clone())
-					.with_motion(Motion::new(8. * Velocity::unit_x(), 0.))
-					.with_effect(COLOR_WHITE, [1., 1., 1., 16.0])
-					.with_color(color, COLOR_TRANSPARENT)
-					.with_ttl(Some(seconds(0.30)))
-					.with_pulse(1.0, 10.)
-					.with_phase(0.0, 1. / <f32 as convert::From<_>>::from(cluster_size))
-					.with_3_faders(Fader::new(0.0, 1.2, 1.0), Fader::default(), Fader::default())
-					.with_cluster_size(cluster_size)
-					.with_trail_length(5)
-					.with_acceleration(-5. * Velocity::unit_y())
-					.with_lifespan(seconds(3.0)),
-			};
-			self.emitters.insert(emitter.id, Box::new(emitter));
+			self.add_emitter(source);
 		}
 
+		self.rng = world.fork_rng();
+
 		// Player trail
 		if let Some(player_agent_id) = world.get_player_agent_id() {
 			if self.emitters.is_empty() {
@@ -443,7 +471,7 @@ impl System for ParticleSystem {
 				}
 				world.add_particle(world::particle::Particle::new(
 					particle.transform.clone(),
-					particle.motion.velocity.normalize(),
+					particle.motion.velocity,
 					particle_batch.tag,
 					particle.trail.iter().cloned().collect::<Vec<_>>().into_boxed_slice(),
 					faders,
@@ -453,6 +481,7 @@ impl System for ParticleSystem {
 				));
 			}
 		}
+		world.set_emitters(self.emitters.values().filter_map(|emitter| emitter.snapshot()).collect());
 	}
 }
 
@@ -462,16 +491,65 @@ impl Default for ParticleSystem {
 		ParticleSystem {
 			id_counter: 0,
 			inbox: None,
-			emitters: HashMap::new(),
-			particles: HashMap::new(),
+			emitters: BTreeMap::new(),
+			particles: BTreeMap::new(),
 			dt: seconds(0.),
 			simulation_clock: TimerStopwatch::new(&simulation_timer),
 			simulation_timer,
+			rng: XorShiftRng::from_seed(0),
 		}
 	}
 }
 
 impl ParticleSystem {
+	/// Builds an emitter in the given style
+	fn add_emitter(&mut self, source: world::particle::Emitter) {
+		let emitter = match source.style {
+			EmitterStyle::Explosion { cluster_size, color } => SimpleEmitter::new(self.next_id())
+				.with_transform(source.transform.clone())
+				.with_motion(Motion::new(10. * Velocity::unit_x(), 0.))
+				.with_color(color, COLOR_TRANSPARENT)
+				.with_ttl(Some(seconds(0.5)))
+				.with_cluster_size(cluster_size)
+				.with_cluster_spread(0.5)
+				.with_2_faders(Fader::new(0.0, 2.0, 1.0), Fader::new(1.0, 0.7, 0.1))
+				.with_lifespan(seconds(3.0)),
+			EmitterStyle::Ping { color } => SimpleEmitter::new(self.next_id())
+				.with_attached_to(source.attached_to)
+				.with_transform(source.transform.clone())
+				.with_color(color, COLOR_TRANSPARENT)
+				.with_ttl(Some(seconds(0.33)))
+				.with_4_faders(
+					Fader::new(0.0, 4.0, 1.0),
+					Fader::new(0.5, 0.7, 5.0),
+					Fader::flat(1.),
+					Fader::new(10., 1., 5.),
+				)
+				.with_cluster_size(1)
+				.with_lifespan(seconds(3.0)),
+			EmitterStyle::Sparkle { cluster_size, color } => SimpleEmitter::new(self.next_id())
+				.with_jitter(0.)
+				.with_transform(source.transform.clone())
+				.with_motion(Motion::new(8. * Velocity::unit_x(), 0.))
+				.with_effect(COLOR_WHITE, [1., 1., 1., 16.0])
+				.with_color(color, COLOR_TRANSPARENT)
+				.with_ttl(Some(seconds(0.30)))
+				.with_pulse(1.0, 10.)
+				.with_phase(0.0, 1. / <f32 as convert::From<_>>::from(cluster_size))
+				.with_3_faders(Fader::new(0.0, 1.2, 1.0), Fader::default(), Fader::default())
+				.with_cluster_size(cluster_size)
+				.with_trail_length(5)
+				.with_acceleration(-5. * Velocity::unit_y())
+				.with_lifespan(seconds(3.0)),
+		};
+		let emitter = match source.ttl {
+			Some(_) => emitter.with_ttl(source.ttl),
+			None => emitter,
+		}
+		.with_source(source);
+		self.emitters.insert(emitter.id, Box::new(emitter));
+	}
+
 	fn next_id(&mut self) -> obj::Id {
 		self.id_counter += 1;
 		self.id_counter
@@ -482,7 +560,7 @@ impl ParticleSystem {
 		let mut expired: Vec<usize> = Vec::new();
 
 		for (id, emitter) in &mut self.emitters {
-			let alive = emitter.emit(dt, &mut self.id_counter, &mut self.particles);
+			let alive = emitter.emit(dt, &mut self.rng, &mut self.id_counter, &mut self.particles);
 			if !alive {
 				expired.push(*id);
 			}
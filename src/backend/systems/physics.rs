@@ -6,6 +6,8 @@ use backend::obj;
 use backend::obj::*;
 use backend::world;
 use backend::world::agent;
+use backend::world::agent::TypedAgent;
+use backend::world::boundary::Boundary;
 use backend::world::segment;
 use backend::world::segment::Intent;
 use backend::world::segment::PilotRotation;
@@ -33,10 +35,12 @@ type ContactSet = Rc<RefCell<HashMap<agent::Key, agent::Key>>>;
 pub struct PhysicsSystem {
 	world: b2::World<AgentData>,
 	initial_extent: Rect,
+	boundary: Boundary,
 	inbox: Option<Inbox>,
 	handles: HashMap<agent::Key, b2::BodyHandle>,
 	touched: ContactSet,
 	picked: HashSet<Id>,
+	drag_coefficient: f32,
 }
 
 #[allow(unused)]
@@ -65,6 +69,7 @@ impl System for PhysicsSystem {
 
 	fn init(&mut self, world: &world::World) {
 		self.initial_extent = world.extent;
+		self.boundary = world.boundary;
 		self.init_extent();
 	}
 
@@ -107,6 +112,7 @@ impl System for PhysicsSystem {
 			None => Vec::new(),
 		};
 		self.picked.clear();
+		self.drag_coefficient = world.config().drag_coefficient;
 		for message in messages {
 			if let Message::Event(Event::PickMinion(position)) = message {
 				let picked = self.pick(position);
@@ -148,7 +154,7 @@ impl System for PhysicsSystem {
 						if let Some(force) = force {
 							let linear_velocity = from_vec2(*(*body).linear_velocity());
 							let speed2 = linear_velocity.magnitude2();
-							let drag_factor = (1. - speed2 * DRAG_COEFFICIENT).min(1.).max(0.);
+							let drag_factor = (1. - speed2 * self.drag_coefficient).clamp(0., 1.);
 							dynamic_updates.push((h, Force(center, to_vec2(force * drag_factor))));
 						}
 						match *target_angle {
@@ -195,6 +201,7 @@ impl System for PhysicsSystem {
 			}
 		}
 		self.world.step(dt, 8, 3);
+		self.confine();
 	}
 
 	fn export(&self, world: &mut world::World, outbox: &dyn Outbox) {
@@ -230,10 +237,12 @@ impl Default for PhysicsSystem {
 		PhysicsSystem {
 			inbox: None,
 			initial_extent: Rect::default(),
+			boundary: Boundary::default(),
 			world: Self::new_world(touched.clone()),
 			handles: HashMap::with_capacity(5000),
 			picked: HashSet::with_capacity(100),
 			touched,
+			drag_coefficient: DRAG_COEFFICIENT,
 		}
 	}
 }
@@ -246,22 +255,79 @@ impl PhysicsSystem {
 	fn v2p(p: b2::Vec2) -> Position { Position::new(p.x, p.y) }
 
 	fn init_extent(&mut self) {
-		let extent = self.initial_extent;
+		let walls = match self.boundary.walls(&self.initial_extent) {
+			Some(walls) => walls,
+			None => return,
+		};
 		let mut f_def = b2::FixtureDef::new();
 		let mut b_def = b2::BodyDef::new();
 		b_def.body_type = b2::BodyType::Static;
 		let refs = agent::Key::with_id(0xFFFF_FFFFusize);
 		let handle = self.world.create_body_with(&b_def, refs);
 
-		let mut rect = b2::ChainShape::new();
-		rect.create_loop(&[
-			Self::p2v(extent.bottom_left()),
-			Self::p2v(extent.bottom_right()),
-			Self::p2v(extent.top_right()),
-			Self::p2v(extent.top_left()),
-		]);
+		let mut chain = b2::ChainShape::new();
+		chain.create_loop(&walls.into_iter().map(Self::p2v).collect::<Vec<_>>());
 
-		self.world.body_mut(handle).create_fixture_with(&rect, &mut f_def, refs);
+		self.world.body_mut(handle).create_fixture_with(&chain, &mut f_def, refs);
+	}
+
+	/// Moves whole agents back inside the walls, bouncing them off, or to the
+	/// opposite side of a wrapping world
+	fn confine(&mut self) {
+		let extent = self.initial_extent;
+		let boundary = self.boundary;
+		if boundary == Boundary::Kill {
+			return;
+		}
+		// agents move as one, following their first segment when wrapping, or
+		// the one furthest past the walls otherwise, so that joints hold
+		let mut offsets: HashMap<obj::Id, Position> = HashMap::new();
+		for (_, b) in self.world.bodies() {
+			let body = b.borrow();
+			let key = (*body).user_data();
+			let agent_id = key.agent_id;
+			if boundary == Boundary::Wrap && key.segment_index != 0 {
+				continue;
+			}
+			let p = Self::v2p(*(*body).position());
+			let offset = boundary.confine(&extent, p) - p;
+			if offset.magnitude2() > offsets.get(&agent_id).map(|o| o.magnitude2()).unwrap_or(0.) {
+				offsets.insert(agent_id, offset);
+			}
+		}
+		if offsets.is_empty() {
+			return;
+		}
+		let mut updates = Vec::new();
+		for (h, b) in self.world.bodies() {
+			let body = b.borrow();
+			let agent_id = (*body).user_data().agent_id;
+			if let Some(offset) = offsets.get(&agent_id) {
+				let p = Self::v2p(*(*body).position()) + offset;
+				let velocity = if boundary == Boundary::Wrap {
+					None
+				} else {
+					// only the motion through the wall is reflected
+					let normal = -offset.normalize();
+					let velocity = Self::v2p(*(*body).linear_velocity());
+					let outwards = velocity.dot(normal);
+					if outwards > 0. {
+						Some(velocity - normal * (2. * outwards))
+					} else {
+						None
+					}
+				};
+				updates.push((h, p, velocity));
+			}
+		}
+		for (h, p, velocity) in updates {
+			let b = &mut self.world.body_mut(h);
+			let angle = b.angle();
+			b.set_transform(&Self::p2v(p), angle);
+			if let Some(velocity) = velocity {
+				b.set_linear_velocity(&Self::p2v(velocity));
+			}
+		}
 	}
 
 	fn refresh_registration(&mut self, agent: &world::agent::Agent) {
@@ -396,7 +462,12 @@ impl PhysicsSystem {
 
 				let transform = segment.transform();
 				let mut b_def = b2::BodyDef::new();
-				b_def.body_type = b2::BodyType::Dynamic;
+				// props are obstacles which nothing can push around
+				b_def.body_type = if object_id.type_of() == agent::AgentType::Prop {
+					b2::BodyType::Static
+				} else {
+					b2::BodyType::Dynamic
+				};
 				b_def.linear_damping = material.linear_damping;
 				b_def.angular_damping = material.angular_damping;
 				b_def.angle = transform.angle;
@@ -0,0 +1,62 @@
+use super::*;
+use app::constants::*;
+use backend::obj::Id;
+use backend::world;
+use backend::world::agent;
+use backend::world::gen::Dna;
+use backend::world::species::SpeciesMap;
+use core::clock::*;
+
+/// Regroups the live minions into species at regular intervals
+pub struct SpeciesSystem {
+	timer: SimulationTimer,
+	hourglass: Hourglass,
+	minions: Vec<(Id, Dna)>,
+	species: Option<SpeciesMap>,
+}
+
+impl System for SpeciesSystem {
+	fn clear(&mut self) {
+		self.minions.clear();
+		self.species = None;
+	}
+
+	fn import(&mut self, world: &world::World) {
+		if self.hourglass.flip_if_expired(&self.timer) {
+			self.minions = world
+				.agents(agent::AgentType::Minion)
+				.values()
+				.filter(|a| a.state.is_alive())
+				.map(|a| (a.id(), a.dna().clone()))
+				.collect();
+			self.species = Some(world.species().clone());
+		} else {
+			self.species = None;
+		}
+	}
+
+	fn update(&mut self, _: &dyn world::AgentState, dt: Seconds) {
+		self.timer.tick(dt);
+		if let Some(ref mut species) = self.species {
+			species.cluster(&self.minions);
+		}
+	}
+
+	fn export(&self, world: &mut world::World, _: &dyn Outbox) {
+		if let Some(ref species) = self.species {
+			*world.species_mut() = species.clone();
+		}
+	}
+}
+
+impl Default for SpeciesSystem {
+	fn default() -> Self {
+		let timer = SimulationTimer::new();
+		SpeciesSystem {
+			hourglass: Hourglass::new(seconds(SPECIES_INTERVAL), &timer),
+			timer,
+			minions: Vec::new(),
+			species: None,
+		}
+	}
+}
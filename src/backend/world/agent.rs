@@ -2,6 +2,7 @@ use app::constants::*;
 use backend::obj;
 use backend::obj::*;
 use backend::world::gen::Dna;
+use backend::world::lineage::Lineage;
 use backend::world::segment;
 use backend::world::segment::Segment;
 use core::clock::*;
@@ -10,7 +11,7 @@ use core::geometry::*;
 use core::util;
 use num::Float;
 use num::FromPrimitive;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::f32;
 use std::fmt;
 
@@ -53,7 +54,7 @@ impl TypedAgent for Id {
 }
 
 enum_from_primitive! {
-	#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+	#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
 	pub enum AgentType {
 		Minion,
 		Spore,
@@ -240,6 +241,7 @@ pub struct State {
 	target_position: Position,
 	limits: Limits,
 	foreign_dna: Option<Dna>,
+	lineage: Lineage,
 	trajectory: util::History<Position>,
 }
 
@@ -247,6 +249,8 @@ impl State {
 	#[inline]
 	pub fn lifecycle(&self) -> &Hourglass { &self.lifecycle }
 
+	pub fn set_lifecycle(&mut self, lifecycle: Hourglass) { self.lifecycle = lifecycle }
+
 	pub fn energy(&self) -> f32 { self.energy }
 
 	pub fn energy_ratio(&self) -> f32 { self.energy / self.limits.max_energy }
@@ -291,10 +295,19 @@ impl State {
 
 	pub fn is_fertilised(&self) -> bool { self.foreign_dna.is_some() }
 
-	pub fn fertilise(&mut self, dna: &Dna) { self.foreign_dna = Some(dna.clone()); }
+	pub fn fertilise(&mut self, dna: &Dna, by: Id) {
+		self.foreign_dna = Some(dna.clone());
+		self.lineage.co_parent = Some(by);
+	}
 
 	pub fn foreign_dna(&self) -> &Option<Dna> { &self.foreign_dna }
 
+	pub fn set_foreign_dna(&mut self, dna: Option<Dna>) { self.foreign_dna = dna }
+
+	pub fn lineage(&self) -> &Lineage { &self.lineage }
+
+	pub fn set_lineage(&mut self, lineage: Lineage) { self.lineage = lineage }
+
 	pub fn toggle_selection(&mut self) { self.flags ^= Flags::SELECTED; }
 	#[allow(unused)]
 	pub fn select(&mut self) { self.flags |= Flags::SELECTED; }
@@ -329,7 +342,16 @@ impl State {
 
 	pub fn track_position(&mut self, position: Position) { self.trajectory.push(position) }
 
+	/// Most recent position first
 	pub fn trajectory(&self) -> Box<[Position]> { self.trajectory.into_iter().collect::<Vec<_>>().into_boxed_slice() }
+
+	/// Replaces the trajectory with one returned by trajectory()
+	pub fn restore_trajectory(&mut self, trajectory: &[Position]) {
+		self.trajectory.clear();
+		for position in trajectory.iter().rev() {
+			self.trajectory.push(*position);
+		}
+	}
 }
 
 #[derive(Clone)]
@@ -406,6 +428,7 @@ impl Agent {
 				target_position: segments[0].transform.position,
 				limits: Limits { max_energy },
 				foreign_dna: None,
+				lineage: Lineage::default(),
 				trajectory: util::History::new(600),
 			},
 			brain: brain.clone(),
@@ -416,4 +439,4 @@ impl Agent {
 	}
 }
 
-pub type AgentMap = HashMap<Id, Agent>;
+pub type AgentMap = BTreeMap<Id, Agent>;
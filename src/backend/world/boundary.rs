@@ -0,0 +1,196 @@
+//! What happens at the edge of the world
+use app::constants::*;
+use cgmath::InnerSpace;
+use core::geometry::{Position, Rect};
+use std::f32::consts;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+	/// Walls all around the extent, and minions found outside them die
+	#[default]
+	Kill,
+	/// Walls all around the extent, and whatever gets past them is put back
+	/// inside
+	Bounce,
+	/// No walls: whatever leaves from one side comes back from the opposite
+	/// one, and distances are measured across the edges
+	Wrap,
+	/// A round arena, inscribed in the extent, walled like Bounce
+	Circle,
+}
+
+fn wrap(v: f32, min: f32, max: f32) -> f32 {
+	let size = max - min;
+	if size > 0. {
+		min + (v - min).rem_euclid(size)
+	} else {
+		v
+	}
+}
+
+fn shortest(d: f32, size: f32) -> f32 {
+	if size > 0. {
+		d - size * (d / size).round()
+	} else {
+		d
+	}
+}
+
+impl Boundary {
+	pub fn centre(extent: &Rect) -> Position { (extent.min + extent.max) / 2. }
+
+	/// Radius of the circular arena
+	pub fn radius(extent: &Rect) -> f32 { (extent.max.x - extent.min.x).min(extent.max.y - extent.min.y) / 2. }
+
+	/// Corners of the walls, as a closed loop. None if there are no walls
+	pub fn walls(self, extent: &Rect) -> Option<Vec<Position>> {
+		match self {
+			Boundary::Wrap => None,
+			Boundary::Circle => {
+				let (centre, radius) = (Self::centre(extent), Self::radius(extent));
+				Some(
+					(0..BOUNDARY_CIRCLE_SIDES)
+						.map(|i| {
+							let (s, c) = (consts::PI * 2. * i as f32 / BOUNDARY_CIRCLE_SIDES as f32).sin_cos();
+							centre + Position::new(c, s) * radius
+						})
+						.collect(),
+				)
+			}
+			_ => Some(vec![extent.bottom_left(), extent.bottom_right(), extent.top_right(), extent.top_left()]),
+		}
+	}
+
+	pub fn contains(self, extent: &Rect, p: Position) -> bool {
+		match self {
+			Boundary::Circle => (p - Self::centre(extent)).magnitude() <= Self::radius(extent),
+			_ => p.x >= extent.min.x && p.x <= extent.max.x && p.y >= extent.min.y && p.y <= extent.max.y,
+		}
+	}
+
+	/// Where something found at `p` belongs: the nearest point inside the
+	/// walls, or the point on the opposite side when wrapping. Unchanged when
+	/// killing, or if it is already inside
+	pub fn confine(self, extent: &Rect, p: Position) -> Position {
+		if self.contains(extent, p) {
+			return p;
+		}
+		match self {
+			Boundary::Kill => p,
+			Boundary::Bounce =>
+				Position::new(p.x.max(extent.min.x).min(extent.max.x), p.y.max(extent.min.y).min(extent.max.y)),
+			Boundary::Wrap =>
+				Position::new(wrap(p.x, extent.min.x, extent.max.x), wrap(p.y, extent.min.y, extent.max.y)),
+			Boundary::Circle => {
+				let centre = Self::centre(extent);
+				centre + (p - centre).normalize_to(Self::radius(extent))
+			}
+		}
+	}
+
+	/// Shortest displacement from one point to another, which may go across
+	/// the edges when wrapping
+	pub fn delta(self, extent: &Rect, from: Position, to: Position) -> Position {
+		let d = to - from;
+		match self {
+			Boundary::Wrap =>
+				Position::new(shortest(d.x, extent.max.x - extent.min.x), shortest(d.y, extent.max.y - extent.min.y)),
+			_ => d,
+		}
+	}
+
+	/// Offsets at which something within `margin` of an edge is also seen
+	/// across it. Empty unless wrapping
+	pub fn images(self, extent: &Rect, p: Position, margin: f32) -> Vec<Position> {
+		if self != Boundary::Wrap {
+			return Vec::new();
+		}
+		let (width, height) = (extent.max.x - extent.min.x, extent.max.y - extent.min.y);
+		let dx = if p.x - extent.min.x < margin {
+			Some(width)
+		} else if extent.max.x - p.x < margin {
+			Some(-width)
+		} else {
+			None
+		};
+		let dy = if p.y - extent.min.y < margin {
+			Some(height)
+		} else if extent.max.y - p.y < margin {
+			Some(-height)
+		} else {
+			None
+		};
+		let mut images = Vec::new();
+		if let Some(dx) = dx {
+			images.push(Position::new(dx, 0.));
+		}
+		if let Some(dy) = dy {
+			images.push(Position::new(0., dy));
+		}
+		if let (Some(dx), Some(dy)) = (dx, dy) {
+			images.push(Position::new(dx, dy));
+		}
+		images
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn extent() -> Rect { Rect::new(-10., -5., 10., 5.) }
+
+	#[test]
+	fn wrap_into_range() {
+		assert_eq!(wrap(12., -10., 10.), -8.);
+		assert_eq!(wrap(-11., -10., 10.), 9.);
+		assert_eq!(wrap(3., -10., 10.), 3.);
+		assert_eq!(wrap(3., 0., 0.), 3.);
+	}
+
+	#[test]
+	fn shortest_across_the_edge() {
+		assert_eq!(shortest(15., 20.), -5.);
+		assert_eq!(shortest(-15., 20.), 5.);
+		assert_eq!(shortest(4., 20.), 4.);
+		assert_eq!(shortest(4., 0.), 4.);
+	}
+
+	#[test]
+	fn confine_inside_is_unchanged() {
+		let p = Position::new(3., 2.);
+		for boundary in &[Boundary::Kill, Boundary::Bounce, Boundary::Wrap, Boundary::Circle] {
+			assert_eq!(boundary.confine(&extent(), p), p);
+		}
+	}
+
+	#[test]
+	fn confine_outside() {
+		let p = Position::new(12., -7.);
+		assert_eq!(Boundary::Kill.confine(&extent(), p), p);
+		assert_eq!(Boundary::Bounce.confine(&extent(), p), Position::new(10., -5.));
+		assert_eq!(Boundary::Wrap.confine(&extent(), p), Position::new(-8., 3.));
+		let confined = Boundary::Circle.confine(&extent(), Position::new(8., 0.));
+		assert!((confined - Position::new(5., 0.)).magnitude() < 1e-6);
+	}
+
+	#[test]
+	fn delta_wraps_only_when_wrapping() {
+		let (from, to) = (Position::new(-9., 4.), Position::new(9., -4.));
+		assert_eq!(Boundary::Bounce.delta(&extent(), from, to), Position::new(18., -8.));
+		assert_eq!(Boundary::Wrap.delta(&extent(), from, to), Position::new(-2., 2.));
+	}
+
+	#[test]
+	fn images_near_the_edges() {
+		assert!(Boundary::Bounce.images(&extent(), Position::new(-9.5, 4.5), 1.).is_empty());
+		assert!(Boundary::Wrap.images(&extent(), Position::new(0., 0.), 1.).is_empty());
+		assert_eq!(Boundary::Wrap.images(&extent(), Position::new(9.5, 0.), 1.), vec![Position::new(-20., 0.)]);
+		assert_eq!(Boundary::Wrap.images(&extent(), Position::new(-9.5, 4.5), 1.), vec![
+			Position::new(20., 0.),
+			Position::new(0., -10.),
+			Position::new(20., -10.)
+		]);
+	}
+}
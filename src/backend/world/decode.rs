@@ -0,0 +1,214 @@
+//! Explanation of a minion genome in terms of the phenotype it develops into
+use backend::obj::{AttachmentIndex, Rgba, SegmentIndex};
+use backend::world::agent;
+use backend::world::agent::{WeightMatrix, N_WEIGHTS};
+use backend::world::gen::{BitRange, Dna, Generator, Genome};
+use backend::world::phen;
+use core::clock::SimulationTimer;
+use serde_json;
+use serialize::base64::{self, FromBase64, ToBase64};
+use std::io;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Gene {
+	pub name: String,
+	pub bits: BitRange,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentPlan {
+	pub index: SegmentIndex,
+	pub parent: Option<SegmentIndex>,
+	pub attachment_point: Option<AttachmentIndex>,
+	pub shape: String,
+	pub flags: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MinionPhenotype {
+	pub dna: String,
+	pub bit_count: usize,
+	pub gender: u8,
+	pub tint: f32,
+	pub color: Rgba,
+	pub hunger: f32,
+	pub haste: f32,
+	pub prudence: f32,
+	pub fear: f32,
+	pub rest: f32,
+	pub thrust: f32,
+	pub weights_in: WeightMatrix<f32>,
+	pub weights_hidden: WeightMatrix<f32>,
+	pub weights_out: WeightMatrix<f32>,
+	/// Segments in the order they were added, each attached to an earlier one
+	pub body: Vec<SegmentPlan>,
+	/// The bits each trait was read from. The body plan is a single gene, as
+	/// the number of segments and of the reads for each varies
+	pub genes: Vec<Gene>,
+}
+
+/// The traits read by `phen::Minion::develop` before the body plan, in order
+fn gene_names() -> Vec<String> {
+	let mut names = vec!["gender".to_owned(), "tint".to_owned()];
+	for i in 0..N_WEIGHTS {
+		for j in 0..N_WEIGHTS {
+			for matrix in &["weights_in", "weights_hidden", "weights_out"] {
+				names.push(format!("{}[{}][{}]", matrix, i, j));
+			}
+		}
+	}
+	names.extend(["hunger", "haste", "prudence", "fear", "rest", "thrust"].iter().map(|s| s.to_string()));
+	names
+}
+
+/// Develops a minion from the given DNA without adding it to a world. None if
+/// the DNA is empty
+pub fn decode_minion(dna: &[u8]) -> Option<MinionPhenotype> {
+	if dna.is_empty() {
+		return None;
+	}
+	let mut gen = Genome::traced(dna);
+	let minion = phen::phenotype_of(agent::AgentType::Minion).develop(
+		&mut gen,
+		0,
+		agent::InitialState::default(),
+		&SimulationTimer::new(),
+	);
+
+	let mut genes = gene_names()
+		.into_iter()
+		.zip(gen.reads().iter())
+		.map(|(name, bits)| Gene { name, bits: *bits })
+		.collect::<Vec<_>>();
+	let body_reads = gen.reads().iter().skip(genes.len()).collect::<Vec<_>>();
+	if let Some(first) = body_reads.first() {
+		let len = body_reads.iter().map(|r| r.len).sum();
+		genes.push(Gene { name: "body plan".to_owned(), bits: BitRange { start: first.start, len } });
+	}
+
+	// tint is only kept as the color of the minion
+	let mut traits = Genome::copy_from(dna);
+	traits.next_integer::<u8>(0, 3);
+	let tint = traits.next_float(0., 1.);
+
+	let brain = minion.brain();
+	let body = minion
+		.segments()
+		.iter()
+		.map(|segment| SegmentPlan {
+			index: segment.index,
+			parent: segment.attached_to.as_ref().map(|a| a.index),
+			attachment_point: segment.attached_to.as_ref().map(|a| a.attachment_point),
+			shape: segment.mesh.shape.to_string(),
+			flags: format!("{:?}", segment.flags),
+		})
+		.collect();
+	Some(MinionPhenotype {
+		dna: dna.to_base64(base64::STANDARD),
+		bit_count: gen.bit_count(),
+		gender: minion.gender(),
+		tint,
+		color: minion.segments()[0].livery.albedo,
+		hunger: brain.hunger,
+		haste: brain.haste,
+		prudence: brain.prudence,
+		fear: brain.fear,
+		rest: brain.rest,
+		thrust: brain.thrust,
+		weights_in: brain.weights_in,
+		weights_hidden: brain.weights_hidden,
+		weights_out: brain.weights_out,
+		body,
+		genes,
+	})
+}
+
+/// Decodes a base64 DNA string, as found in gene pools and snapshots
+pub fn decode_minion_base64(dna: &str) -> Result<MinionPhenotype, String> {
+	let dna: Dna = dna.from_base64().map_err(|e| e.to_string())?.into_boxed_slice();
+	decode_minion(&dna).ok_or_else(|| "Empty DNA".to_owned())
+}
+
+impl MinionPhenotype {
+	pub fn write_json<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		serde_json::to_writer_pretty(&mut *out, self)?;
+		writeln!(out)
+	}
+
+	pub fn write_text<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		let c = &self.color;
+		writeln!(out, "DNA: {} ({} bits)", self.dna, self.bit_count)?;
+		writeln!(out, "Gender: {}", self.gender)?;
+		writeln!(out, "Tint: {:.3} (rgb {:.2} {:.2} {:.2})", self.tint, c[0], c[1], c[2])?;
+		writeln!(out)?;
+		writeln!(out, "Personality:")?;
+		for (name, value) in &[
+			("hunger", self.hunger),
+			("haste", self.haste),
+			("prudence", self.prudence),
+			("fear", self.fear),
+			("rest", self.rest),
+			("thrust", self.thrust),
+		] {
+			writeln!(out, "  {:<10}{:>7.3}", name, value)?;
+		}
+		for (name, weights) in
+			&[("Input", &self.weights_in), ("Hidden", &self.weights_hidden), ("Output", &self.weights_out)]
+		{
+			writeln!(out)?;
+			writeln!(out, "{} weights:", name)?;
+			for row in weights.iter() {
+				write!(out, " ")?;
+				for w in row {
+					write!(out, " {:>7.3}", w)?;
+				}
+				writeln!(out)?;
+			}
+		}
+		writeln!(out)?;
+		writeln!(out, "Body plan:")?;
+		self.write_segment(out, 0, 1)?;
+		writeln!(out)?;
+		writeln!(out, "Genes:")?;
+		for gene in &self.genes {
+			let end = gene.bits.start + gene.bits.len;
+			if end > self.bit_count {
+				writeln!(
+					out,
+					"  {:<22} bits {:>4}..{:<4} (wraps around)",
+					gene.name,
+					gene.bits.start,
+					end % self.bit_count
+				)?;
+			} else {
+				writeln!(out, "  {:<22} bits {:>4}..{:<4}", gene.name, gene.bits.start, end)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn write_segment<W>(&self, out: &mut W, index: SegmentIndex, depth: usize) -> io::Result<()>
+	where W: io::Write {
+		let segment = &self.body[index as usize];
+		let attachment = match segment.attachment_point {
+			Some(point) => format!(" at {}", point),
+			None => String::new(),
+		};
+		writeln!(
+			out,
+			"{:indent$}#{}{}: {} [{}]",
+			"",
+			segment.index,
+			attachment,
+			segment.shape,
+			segment.flags,
+			indent = depth * 2
+		)?;
+		for child in self.body.iter().filter(|s| s.parent == Some(index)) {
+			self.write_segment(out, child.index, depth + 1)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,239 @@
+//! Changes of the environment over simulated time: seasons, famines and booms,
+//! which vary the food supply and the light
+use core::clock::SecondsValue;
+use std::f32::consts;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpellKind {
+	Famine,
+	Boom,
+}
+
+/// A stretch of time when food is scarcer, or more abundant, than the season
+/// would have it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spell {
+	pub kind: SpellKind,
+	/// Simulated seconds from the beginning of the world
+	pub start: SecondsValue,
+	pub duration: SecondsValue,
+	/// Seconds between two recurrences, 0 if it happens only once
+	#[serde(default)]
+	pub every: SecondsValue,
+	/// Fraction of the food supply lost to a famine, or added by a boom
+	pub strength: f32,
+}
+
+impl Spell {
+	pub fn is_active(&self, t: SecondsValue) -> bool {
+		if t < self.start {
+			return false;
+		}
+		let since = if self.every > 0. { (t - self.start) % self.every } else { t - self.start };
+		since < self.duration
+	}
+
+	fn factor(&self) -> f32 {
+		match self.kind {
+			SpellKind::Famine => (1. - self.strength).max(0.),
+			SpellKind::Boom => 1. + self.strength.max(0.),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+	Spring,
+	Summer,
+	Autumn,
+	Winter,
+}
+
+impl Season {
+	/// Summer is centred on the peak of the food supply, winter on its trough
+	fn of(phase: f32) -> Self {
+		match ((phase + 0.125).rem_euclid(1.) * 4.) as usize {
+			1 => Season::Summer,
+			2 => Season::Autumn,
+			3 => Season::Winter,
+			_ => Season::Spring,
+		}
+	}
+}
+
+/// How the environment changes over time. Leaving everything out keeps it
+/// constant
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+	/// Simulated seconds in a year, 0 for no seasons
+	pub period: SecondsValue,
+	/// How far the food supply swings above and below the average over a
+	/// year, as a fraction of it
+	pub amplitude: f32,
+	/// Delay of the seasons of each feeder after the previous one, as a
+	/// fraction of a year
+	pub feeder_phase: f32,
+	/// How much the charge of the resources follows the food supply, from 0
+	/// (not at all) to 1. The emission rate always follows it
+	pub charge_response: f32,
+	/// How much the light follows the food supply, from 0 (not at all) to 1
+	pub light_response: f32,
+	pub spells: Vec<Spell>,
+}
+
+impl Default for Schedule {
+	fn default() -> Self {
+		Schedule {
+			period: 0.,
+			amplitude: 0.5,
+			feeder_phase: 0.,
+			charge_response: 0.5,
+			light_response: 0.5,
+			spells: Vec::new(),
+		}
+	}
+}
+
+/// The state of the environment at some point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conditions {
+	pub season: Option<Season>,
+	pub spell: Option<SpellKind>,
+	/// Food supply relative to the average, which scales the emission rate
+	pub abundance: f32,
+	/// Scales the charge of the resources
+	pub charge: f32,
+	/// Scales the light
+	pub light: f32,
+}
+
+impl fmt::Display for Conditions {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (self.season, self.spell) {
+			(Some(season), Some(spell)) => write!(f, "{:?}, {:?}", season, spell)?,
+			(Some(season), None) => write!(f, "{:?}", season)?,
+			(None, Some(spell)) => write!(f, "{:?}", spell)?,
+			(None, None) => write!(f, "Constant")?,
+		}
+		write!(f, " {:.0}%", self.abundance * 100.)
+	}
+}
+
+fn respond(abundance: f32, response: f32) -> f32 { 1. + (abundance - 1.) * response }
+
+impl Schedule {
+	/// Fraction of the year gone at a time, with some delay
+	fn phase(&self, t: SecondsValue, delay: f32) -> Option<f32> {
+		if self.period > 0. {
+			Some(((t / self.period) as f32 - delay).rem_euclid(1.))
+		} else {
+			None
+		}
+	}
+
+	/// The conditions of the feeder at a position in the sequence, whose
+	/// seasons are also delayed by its own phase
+	pub fn conditions(&self, t: SecondsValue, feeder: usize, phase: f32) -> Conditions {
+		let phase = self.phase(t, feeder as f32 * self.feeder_phase + phase);
+		let seasonal = phase.map(|p| 1. + self.amplitude * (p * 2. * consts::PI).sin()).unwrap_or(1.);
+		let spells = self.spells.iter().filter(|s| s.is_active(t)).collect::<Vec<_>>();
+		let abundance = spells.iter().fold(seasonal, |a, s| a * s.factor()).max(0.);
+		Conditions {
+			season: phase.map(Season::of),
+			spell: spells.last().map(|s| s.kind),
+			abundance,
+			charge: respond(abundance, self.charge_response).max(0.),
+			light: respond(abundance, self.light_response).max(0.),
+		}
+	}
+
+	/// The conditions of the world as a whole, which are those of a feeder
+	/// with no delay
+	pub fn world_conditions(&self, t: SecondsValue) -> Conditions { self.conditions(t, 0, 0.) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn seasons(period: SecondsValue) -> Schedule { Schedule { period, ..Default::default() } }
+
+	fn spell(kind: SpellKind, every: SecondsValue) -> Spell {
+		Spell { kind, start: 10., duration: 5., every, strength: 0.5 }
+	}
+
+	#[test]
+	fn season_of_each_quarter() {
+		let quarters = [(0., Season::Spring), (0.25, Season::Summer), (0.5, Season::Autumn), (0.75, Season::Winter)];
+		for (phase, season) in quarters.iter() {
+			assert_eq!(Season::of(*phase), *season);
+			assert_eq!(Season::of(*phase + 0.1), *season);
+			assert_eq!(Season::of(*phase - 0.1), *season);
+			assert_eq!(Season::of(*phase + 1.), *season);
+		}
+	}
+
+	#[test]
+	fn one_period_is_a_year() {
+		let schedule = seasons(100.);
+		for t in &[0., 12.5, 25., 40., 75., 99.] {
+			let now = schedule.world_conditions(*t);
+			let next_year = schedule.world_conditions(t + 100.);
+			assert_eq!(now.season, next_year.season, "{}", t);
+			assert!((now.abundance - next_year.abundance).abs() < 1e-4, "{}", t);
+		}
+		assert_eq!(schedule.world_conditions(25.).season, Some(Season::Summer));
+		assert!((schedule.world_conditions(25.).abundance - 1.5).abs() < 1e-4);
+		assert!((schedule.world_conditions(75.).abundance - 0.5).abs() < 1e-4);
+	}
+
+	#[test]
+	fn no_seasons_without_a_period() {
+		let conditions = seasons(0.).world_conditions(25.);
+		assert_eq!((conditions.season, conditions.spell, conditions.abundance), (None, None, 1.));
+	}
+
+	#[test]
+	fn spell_window() {
+		let schedule = Schedule { spells: vec![spell(SpellKind::Famine, 0.)], ..seasons(0.) };
+		let spell_at = |t| {
+			let conditions = schedule.world_conditions(t);
+			(conditions.spell, conditions.abundance)
+		};
+		assert_eq!(spell_at(9.9), (None, 1.));
+		assert_eq!(spell_at(10.), (Some(SpellKind::Famine), 0.5));
+		assert_eq!(spell_at(14.9), (Some(SpellKind::Famine), 0.5));
+		assert_eq!(spell_at(15.), (None, 1.));
+		assert_eq!(spell_at(35.), (None, 1.));
+	}
+
+	#[test]
+	fn recurring_spell() {
+		let boom = spell(SpellKind::Boom, 20.);
+		assert!(!boom.is_active(5.));
+		assert!(boom.is_active(12.) && boom.is_active(32.) && boom.is_active(52.));
+		assert!(!boom.is_active(16.) && !boom.is_active(36.));
+		let schedule = Schedule { spells: vec![boom], ..seasons(0.) };
+		assert_eq!(schedule.world_conditions(33.).abundance, 1.5);
+	}
+
+	#[test]
+	fn feeder_phase_delays_each_feeder() {
+		let schedule = Schedule { feeder_phase: 0.25, ..seasons(100.) };
+		let season = |t, feeder, phase| schedule.conditions(t, feeder, phase).season;
+		assert_eq!(season(25., 0, 0.), Some(Season::Summer));
+		assert_eq!(season(25., 1, 0.), Some(Season::Spring));
+		assert_eq!(season(25., 2, 0.), Some(Season::Winter));
+		assert_eq!(season(25., 3, 0.), Some(Season::Autumn));
+		// a feeder's own phase adds to its delay
+		assert_eq!(season(25., 1, 0.25), Some(Season::Winter));
+		for feeder in 0..4 {
+			let delayed = schedule.conditions(25. + 25. * feeder as SecondsValue, feeder, 0.);
+			assert_eq!(delayed.season, Some(Season::Summer), "{}", feeder);
+		}
+	}
+}
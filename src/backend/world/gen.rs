@@ -1,12 +1,17 @@
+use app::constants::*;
 use backend::obj::*;
+use backend::world::selection;
+use backend::world::selection::Selection;
+use backend::world::species::SpeciesId;
 use csv;
 use num;
 use rand;
-use rand::Rng;
 use serialize::base64::{self, FromBase64, ToBase64};
 use std::cmp;
+use std::collections::HashSet;
 use std::f32::consts;
 use std::fmt;
+use std::io;
 use std::slice::Iter;
 
 pub type Dna = Box<[u8]>;
@@ -17,60 +22,214 @@ fn bit_count(p: usize) -> usize { p << 3 }
 
 fn split_bit(p: usize) -> (usize, u8) { (p >> 3, (p & 0x7) as u8) }
 
+/// Number of differing bits between two DNA strings. Bits past the end of the
+/// shorter one all count as different
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> usize {
+	let common = a.iter().zip(b.iter()).map(|(x, y)| (x ^ y).count_ones() as usize).sum::<usize>();
+	common + bit_count(cmp::max(a.len(), b.len()) - cmp::min(a.len(), b.len()))
+}
+
+/// Columns of a gene pool file, in the order they are read when there is no
+/// header. Only the DNA is required
+pub const GENE_POOL_COLUMNS: [&str; 7] = ["dna", "name", "origin", "generation", "offspring", "species", "notes"];
+
+/// Provenance of a genome in a gene pool file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeneMetadata {
+	pub name: Option<String>,
+	/// The snapshot the genome was taken from
+	pub origin: Option<String>,
+	pub generation: Option<usize>,
+	pub offspring: Option<usize>,
+	pub species: Option<SpeciesId>,
+	pub notes: Option<String>,
+}
+
+impl GeneMetadata {
+	/// One, plus one for each child the genome is known to have had
+	pub fn fitness(&self) -> f32 { 1. + self.offspring.unwrap_or(0) as f32 }
+
+	fn from_fields(fields: &[String], columns: &[usize]) -> Self {
+		let field = |column: usize| {
+			columns
+				.iter()
+				.position(|c| *c == column)
+				.and_then(|i| fields.get(i))
+				.map(|f| f.trim())
+				.filter(|f| !f.is_empty())
+		};
+		GeneMetadata {
+			name: field(1).map(str::to_owned),
+			origin: field(2).map(str::to_owned),
+			generation: field(3).and_then(|f| f.parse().ok()),
+			offspring: field(4).and_then(|f| f.parse().ok()),
+			species: field(5).and_then(|f| f.parse().ok()),
+			notes: field(6).map(str::to_owned),
+		}
+	}
+
+	fn to_fields(&self, dna: &[u8]) -> Vec<String> {
+		fn number(value: Option<usize>) -> String { value.map(|v| v.to_string()).unwrap_or_default() }
+		vec![
+			dna.to_base64(base64::STANDARD),
+			self.name.clone().unwrap_or_default(),
+			self.origin.clone().unwrap_or_default(),
+			number(self.generation),
+			number(self.offspring),
+			number(self.species),
+			self.notes.clone().unwrap_or_default(),
+		]
+	}
+}
+
+/// Writes a gene pool file with a header and all the metadata columns
+pub fn write_gene_pool<'a, W, I>(out: W, genes: I) -> io::Result<()>
+where
+	W: io::Write,
+	I: Iterator<Item = (&'a [u8], &'a GeneMetadata)>, {
+	let csv_error = |e: csv::Error| io::Error::other(e.to_string());
+	let mut csv = csv::Writer::from_writer(out);
+	csv.write(GENE_POOL_COLUMNS.iter()).map_err(csv_error)?;
+	for (dna, metadata) in genes {
+		csv.write(metadata.to_fields(dna).iter()).map_err(csv_error)?;
+	}
+	csv.flush().map_err(csv_error)
+}
+
 pub struct GenePool {
 	gene_pool: Box<[Dna]>,
+	metadata: Box<[GeneMetadata]>,
+	fitness: Box<[f32]>,
 	round_robin: usize,
+	selection: Box<dyn Selection>,
+	mutation_rate: f32,
 }
 
 impl GenePool {
-	pub fn gene_pool_iter(&self) -> Iter<Dna> { self.gene_pool.iter() }
+	pub fn gene_pool_iter(&self) -> Iter<'_, Dna> { self.gene_pool.iter() }
 	pub fn gene_pool_index(&self) -> usize { self.round_robin }
+	/// Provenance of each genome, in the same order as gene_pool_iter()
+	pub fn metadata_iter(&self) -> Iter<'_, GeneMetadata> { self.metadata.iter() }
+	pub fn fitness(&self) -> &[f32] { &self.fitness }
+
+	/// Fitness of each genome, in the same order as gene_pool_iter(). Until
+	/// set, it is derived from the offspring counts in the metadata
+	pub fn set_fitness(&mut self, index: usize, fitness: f32) {
+		if let Some(f) = self.fitness.get_mut(index) {
+			*f = fitness;
+		}
+	}
+
+	pub fn set_selection(&mut self, selection: Box<dyn Selection>) { self.selection = selection; }
+
+	pub fn set_mutation_rate(&mut self, mutation_rate: f32) { self.mutation_rate = mutation_rate; }
 
 	pub fn populate_from_base64(&mut self, base64: &[String], round_robin: usize) {
-		self.gene_pool =
-			base64.iter().map(|s| s.from_base64().unwrap().into_boxed_slice()).collect::<Vec<_>>().into_boxed_slice();
+		let gene_pool = base64.iter().map(|s| s.from_base64().unwrap().into_boxed_slice()).collect::<Vec<_>>();
+		let metadata = vec![GeneMetadata::default(); gene_pool.len()];
+		self.populate(gene_pool, metadata);
 		self.round_robin = round_robin;
 	}
 
 	pub fn parse_from_base64(base64: &[&str]) -> Self {
-		GenePool {
-			gene_pool: base64
-				.iter()
-				.map(|s| s.from_base64().unwrap().into_boxed_slice())
-				.collect::<Vec<_>>()
-				.into_boxed_slice(),
-			round_robin: 0,
-		}
+		let gene_pool = base64.iter().map(|s| s.from_base64().unwrap().into_boxed_slice()).collect::<Vec<_>>();
+		Self::new(&gene_pool)
 	}
 
-	pub fn parse_from_resource(data: &[u8]) -> Self {
+	/// Reads a gene pool file. The first column is always the DNA in base64;
+	/// metadata columns follow, in the order of the header if the first line
+	/// starts with `dna`, or in that of GENE_POOL_COLUMNS otherwise. Errors
+	/// give the line of the first invalid row
+	pub fn parse_from_resource(data: &[u8]) -> io::Result<Self> {
+		let invalid_row = |line: usize, e: &dyn ToString| {
+			io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, e.to_string()))
+		};
 		let mut gene_pool = Vec::new();
-		let mut csv = csv::Reader::from_bytes(data).has_headers(false);
-		for row in csv.records() {
-			let fields = row.unwrap();
-			gene_pool.push(fields[0].from_base64().unwrap().into_boxed_slice());
+		let mut metadata = Vec::new();
+		let mut columns = (0..GENE_POOL_COLUMNS.len()).collect::<Vec<_>>();
+		let mut csv = csv::Reader::from_bytes(data).has_headers(false).flexible(true);
+		for (i, row) in csv.records().enumerate() {
+			let fields = row.map_err(|e| invalid_row(i + 1, &e))?;
+			if fields.is_empty() || fields[0].trim().is_empty() {
+				continue;
+			}
+			if i == 0 && fields[0].trim().eq_ignore_ascii_case(GENE_POOL_COLUMNS[0]) {
+				columns = fields
+					.iter()
+					.map(|f| {
+						let name = f.trim().to_lowercase();
+						GENE_POOL_COLUMNS.iter().position(|c| *c == name).unwrap_or(usize::MAX)
+					})
+					.collect();
+				continue;
+			}
+			let dna = fields[0].trim().from_base64().map_err(|e| invalid_row(i + 1, &e))?;
+			gene_pool.push(dna.into_boxed_slice());
+			metadata.push(GeneMetadata::from_fields(&fields, &columns));
 		}
-		GenePool { gene_pool: gene_pool.to_vec().into_boxed_slice(), round_robin: 0 }
+		Ok(Self::with_metadata(gene_pool, metadata))
+	}
+
+	/// All the genomes of the given pools, in order. Only the first copy of a
+	/// genome found in more than one pool is kept, with its metadata
+	pub fn merge(pools: &[GenePool]) -> Self {
+		let mut seen = HashSet::new();
+		let (gene_pool, metadata) = pools
+			.iter()
+			.flat_map(|pool| pool.gene_pool_iter().zip(pool.metadata_iter()))
+			.filter(|(dna, _)| seen.insert((*dna).clone()))
+			.map(|(dna, metadata)| (dna.clone(), metadata.clone()))
+			.unzip();
+		Self::with_metadata(gene_pool, metadata)
+	}
+
+	/// Writes the genomes and their metadata as a gene pool file
+	pub fn write<W>(&self, out: W) -> io::Result<()>
+	where W: io::Write {
+		write_gene_pool(out, self.gene_pool_iter().map(|dna| &dna[..]).zip(self.metadata_iter()))
 	}
 
 	pub fn len(&self) -> usize { self.gene_pool.len() }
 
-	#[allow(dead_code)]
 	pub fn new(gene_pool: &[Dna]) -> Self {
-		GenePool { gene_pool: gene_pool.to_vec().into_boxed_slice(), round_robin: 0 }
+		Self::with_metadata(gene_pool.to_vec(), vec![GeneMetadata::default(); gene_pool.len()])
 	}
 
-	pub fn randomize(&mut self) {
-		let mut rnd = Randomizer::new();
+	pub fn with_metadata(gene_pool: Vec<Dna>, metadata: Vec<GeneMetadata>) -> Self {
+		let mut pool = GenePool {
+			gene_pool: Box::new([]),
+			metadata: Box::new([]),
+			fitness: Box::new([]),
+			round_robin: 0,
+			selection: Box::new(selection::RoundRobin),
+			mutation_rate: MUTATION_RATE,
+		};
+		pool.populate(gene_pool, metadata);
+		pool
+	}
+
+	fn populate(&mut self, gene_pool: Vec<Dna>, metadata: Vec<GeneMetadata>) {
+		self.fitness = metadata.iter().map(GeneMetadata::fitness).collect::<Vec<_>>().into_boxed_slice();
+		self.gene_pool = gene_pool.into_boxed_slice();
+		self.metadata = metadata.into_boxed_slice();
+	}
+
+	pub fn randomize<R: rand::Rng>(&mut self, rng: &mut R) {
+		let mut rnd = Randomizer::new(rng);
 		self.gene_pool[self.round_robin] = rnd.seed().dna_cloned();
 	}
 
-	pub fn next(&mut self) -> Genome {
-		let gen = Genome::copy_from(&self.gene_pool[self.round_robin].clone());
-		let mutated = gen.mutate(&mut rand::thread_rng());
-		self.gene_pool[self.round_robin] = mutated.dna_cloned();
-		self.round_robin = (self.round_robin + 1) % self.gene_pool.len();
-		gen
+	pub fn next<R: rand::Rng>(&mut self, rng: &mut R) -> Genome { self.select(rng).1 }
+
+	/// Picks a genome with the selection policy, and mutates the stored copy.
+	/// Returns its index along with the genome as it was before the mutation
+	pub fn select<R: rand::Rng>(&mut self, rng: &mut R) -> (usize, Genome) {
+		let index = self.selection.select(&self.fitness, self.round_robin, rng) % self.gene_pool.len();
+		let gen = Genome::copy_from(&self.gene_pool[index].clone());
+		let mutated = gen.mutate_with_rate(rng, self.mutation_rate);
+		self.gene_pool[index] = mutated.dna_cloned();
+		self.round_robin = (index + 1) % self.gene_pool.len();
+		(index, gen)
 	}
 }
 
@@ -166,12 +325,15 @@ where R: rand::Rng {
 	rng: R,
 }
 
-#[allow(dead_code)]
-impl Randomizer<rand::ThreadRng> {
-	pub fn new() -> Randomizer<rand::ThreadRng> { Randomizer { rng: rand::thread_rng() } }
+impl<R> Randomizer<R>
+where R: rand::Rng
+{
+	pub fn new(rng: R) -> Randomizer<R> { Randomizer { rng } }
 }
 
-impl Generator for Randomizer<rand::ThreadRng> {
+impl<R> Generator for Randomizer<R>
+where R: rand::Rng
+{
 	fn next_float<T>(&mut self, min: T, max: T) -> T
 	where T: rand::Rand + num::Float {
 		self.rng.gen::<T>() * (max - min) + min
@@ -197,19 +359,38 @@ where R: rand::Rng
 	}
 }
 
+/// Bits read at once from a genome. Reads past the last bit wrap around to the
+/// first
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct BitRange {
+	pub start: usize,
+	pub len: usize,
+}
+
 #[derive(Clone)]
 pub struct Genome {
 	dna: Box<[u8]>,
 	ptr: usize,
 	bit_count: usize,
+	reads: Option<Vec<BitRange>>,
 }
 
 impl Genome {
 	pub fn copy_from(dna: &[u8]) -> Self {
-		Genome { ptr: 0, bit_count: bit_count(dna.len()), dna: dna.to_owned().into_boxed_slice() }
+		Genome { ptr: 0, bit_count: bit_count(dna.len()), dna: dna.to_owned().into_boxed_slice(), reads: None }
+	}
+
+	pub fn new(dna: Vec<u8>) -> Self {
+		Genome { ptr: 0, bit_count: bit_count(dna.len()), dna: dna.into_boxed_slice(), reads: None }
 	}
 
-	pub fn new(dna: Vec<u8>) -> Self { Genome { ptr: 0, bit_count: bit_count(dna.len()), dna: dna.into_boxed_slice() } }
+	/// A genome which keeps track of the bits it is read from
+	pub fn traced(dna: &[u8]) -> Self { Genome { reads: Some(Vec::new()), ..Self::copy_from(dna) } }
+
+	/// Every read from a traced genome, in order
+	pub fn reads(&self) -> &[BitRange] { self.reads.as_deref().unwrap_or(&[]) }
+
+	pub fn bit_count(&self) -> usize { self.bit_count }
 
 	#[inline]
 	fn next_bit(&mut self) -> u8 {
@@ -221,6 +402,9 @@ impl Genome {
 
 	#[inline]
 	fn next_bits(&mut self, n: u8) -> i64 {
+		if let Some(ref mut reads) = self.reads {
+			reads.push(BitRange { start: self.ptr, len: usize::from(n) });
+		}
 		//use std::iter;
 		//iter::repeat_with(|| i64::from(self.next_bit())).take(usize::from(n)).fold(0,
 		// |a, bit| a << 1 | bit)
@@ -269,9 +453,12 @@ impl Genome {
 		Genome::new(new_genes)
 	}
 
-	pub fn mutate<R: rand::Rng>(&self, rng: &mut R) -> Self {
+	pub fn mutate<R: rand::Rng>(&self, rng: &mut R) -> Self { self.mutate_with_rate(rng, MUTATION_RATE) }
+
+	/// Flips up to `rate` bits per byte of DNA, at random
+	pub fn mutate_with_rate<R: rand::Rng>(&self, rng: &mut R, rate: f32) -> Self {
 		let mut new_genes = self.dna.to_vec();
-		let n_mutations = rng.gen::<usize>() % (new_genes.len() / 8 + 1);
+		let n_mutations = rng.gen::<usize>() % ((new_genes.len() as f32 * rate.max(0.)) as usize + 1);
 		for _ in 0..n_mutations {
 			let (byte, bit) = split_bit(rng.gen::<usize>() % self.bit_count);
 			new_genes[byte] ^= 1 << bit;
@@ -306,4 +493,35 @@ impl Generator for Genome {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+	use super::*;
+
+	fn pool(csv: &str) -> GenePool { GenePool::parse_from_resource(csv.as_bytes()).unwrap() }
+
+	fn names(pool: &GenePool) -> Vec<Option<&str>> { pool.metadata_iter().map(|m| m.name.as_deref()).collect() }
+
+	#[test]
+	fn parse_with_header() {
+		let pool = pool("dna,offspring,name\nAAEC,3,first\nAQID,,second\n");
+		assert_eq!(pool.len(), 2);
+		assert_eq!(names(&pool), vec![Some("first"), Some("second")]);
+		assert_eq!(pool.fitness(), &[4., 1.]);
+	}
+
+	#[test]
+	fn parse_reports_the_line() {
+		let e = GenePool::parse_from_resource(b"dna,name\nAAEC,first\n!!!,second\n").err().unwrap();
+		assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+		assert!(e.to_string().starts_with("line 3:"), "{}", e);
+	}
+
+	#[test]
+	fn merge_keeps_the_first_copy() {
+		let first = pool("dna,name\nAAEC,first\nAQID,shared\n");
+		let second = pool("dna,name\nAQID,copy\nAgME,second\n");
+		let merged = GenePool::merge(&[first, second]);
+		let dnas = merged.gene_pool_iter().map(|dna| dna.to_base64(base64::STANDARD)).collect::<Vec<_>>();
+		assert_eq!(dnas, vec!["AAEC", "AQID", "AgME"]);
+		assert_eq!(names(&merged), vec![Some("first"), Some("shared"), Some("second")]);
+	}
+}
@@ -0,0 +1,238 @@
+//! Ancestry of the minions born during a run, and its export as a family tree
+use app::constants::*;
+use backend::obj::Id;
+use core::clock::SecondsValue;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lineage {
+	pub parent: Option<Id>,
+	/// The minion which fertilised the spore this agent hatched from
+	pub co_parent: Option<Id>,
+	pub generation: usize,
+	pub birth_time: SecondsValue,
+}
+
+impl Lineage {
+	pub fn founder(birth_time: SecondsValue) -> Self { Lineage { birth_time, ..Default::default() } }
+
+	pub fn offspring_of(parent: Id, parent_lineage: &Lineage, birth_time: SecondsValue) -> Self {
+		Lineage { parent: Some(parent), co_parent: None, generation: parent_lineage.generation + 1, birth_time }
+	}
+}
+
+/// The minions born since the beginning of a run which are still alive, or
+/// have living descendants. It is saved as a list of members, as CBOR would
+/// write the ids as integer keys
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Member>", into = "Vec<Member>")]
+pub struct FamilyTree {
+	members: BTreeMap<Id, Member>,
+	/// Size of the tree after it was last pruned
+	pruned_size: usize,
+}
+
+/// A member of a family tree, with the number of children born to it, and to
+/// founders the number of their descendants, including the pruned ones
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Member {
+	pub id: Id,
+	#[serde(flatten)]
+	pub lineage: Lineage,
+	/// The earliest ancestor in the tree, or the member itself. Snapshots older
+	/// than the counts have none
+	#[serde(default)]
+	pub founder: Option<Id>,
+	#[serde(default)]
+	pub offspring: usize,
+	#[serde(default)]
+	pub descendants: usize,
+}
+
+impl From<Vec<Member>> for FamilyTree {
+	fn from(members: Vec<Member>) -> Self {
+		let mut tree = FamilyTree { members: members.into_iter().map(|m| (m.id, m)).collect(), pruned_size: 0 };
+		// parents are born, and have their ids assigned, before their children
+		let uncounted = tree.members.values().filter(|m| m.founder.is_none()).map(|m| m.id).collect::<Vec<_>>();
+		for id in uncounted {
+			tree.count_birth(id);
+		}
+		tree
+	}
+}
+
+impl From<FamilyTree> for Vec<Member> {
+	fn from(tree: FamilyTree) -> Self { tree.members.into_values().collect() }
+}
+
+impl FamilyTree {
+	pub fn insert(&mut self, id: Id, lineage: Lineage) {
+		if let Some(member) = self.members.get_mut(&id) {
+			member.lineage = lineage;
+			return;
+		}
+		self.members.insert(id, Member { id, lineage, founder: None, offspring: 0, descendants: 0 });
+		self.count_birth(id);
+	}
+
+	/// Counts a member as a child of its parent and a descendant of its founder
+	fn count_birth(&mut self, id: Id) {
+		let founder = match self.members[&id].lineage.parent.and_then(|p| self.members.get_mut(&p)) {
+			Some(parent) => {
+				parent.offspring += 1;
+				parent.founder.unwrap_or(parent.id)
+			}
+			None => id,
+		};
+		if founder != id {
+			if let Some(founder) = self.members.get_mut(&founder) {
+				founder.descendants += 1;
+			}
+		}
+		if let Some(member) = self.members.get_mut(&id) {
+			member.founder = Some(founder);
+		}
+	}
+
+	pub fn get(&self, id: Id) -> Option<&Lineage> { self.members.get(&id).map(|m| &m.lineage) }
+
+	pub fn contains(&self, id: Id) -> bool { self.members.contains_key(&id) }
+
+	pub fn len(&self) -> usize { self.members.len() }
+
+	pub fn is_empty(&self) -> bool { self.members.is_empty() }
+
+	pub fn clear(&mut self) {
+		self.members.clear();
+		self.pruned_size = 0;
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&Id, &Lineage)> { self.members.iter().map(|(id, m)| (id, &m.lineage)) }
+
+	/// Number of children born to a member
+	pub fn offspring(&self, id: Id) -> usize { self.members.get(&id).map_or(0, |m| m.offspring) }
+
+	/// Number of descendants born to a founder
+	pub fn descendants(&self, id: Id) -> usize { self.members.get(&id).map_or(0, |m| m.descendants) }
+
+	/// Whether the tree has doubled in size since it was last pruned
+	pub fn is_overgrown(&self) -> bool { self.members.len() > FAMILY_TREE_PRUNE_SIZE.max(self.pruned_size * 2) }
+
+	/// Drops the members which are neither alive nor ancestors of a living one
+	pub fn prune(&mut self, alive: &BTreeSet<Id>) {
+		let mut kept = BTreeSet::new();
+		for id in alive {
+			let mut ancestor = Some(*id);
+			while let Some(id) = ancestor.filter(|a| self.members.contains_key(a) && kept.insert(*a)) {
+				ancestor = self.members[&id].lineage.parent;
+			}
+		}
+		self.members.retain(|id, _| kept.contains(id));
+		self.pruned_size = self.members.len();
+	}
+
+	/// Members with no known parent
+	pub fn roots(&self) -> Vec<Id> {
+		self.members
+			.iter()
+			.filter(|&(_, m)| m.lineage.parent.map(|p| !self.members.contains_key(&p)).unwrap_or(true))
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
+	pub fn children(&self) -> BTreeMap<Id, Vec<Id>> {
+		let mut children: BTreeMap<Id, Vec<Id>> = BTreeMap::new();
+		for (id, member) in &self.members {
+			if let Some(parent) = member.lineage.parent.filter(|p| self.members.contains_key(p)) {
+				children.entry(parent).or_default().push(*id);
+			}
+		}
+		children
+	}
+
+	/// GraphML for .graphml files, Newick otherwise
+	pub fn save(&self, file_path: &path::Path) -> io::Result<()> {
+		let mut out = io::BufWriter::new(fs::File::create(file_path)?);
+		match file_path.extension().and_then(OsStr::to_str) {
+			Some("graphml") => self.write_graphml(&mut out),
+			_ => self.write_newick(&mut out),
+		}
+	}
+
+	/// A single tree in Newick format, with all the founders attached to an
+	/// unnamed root. Branch lengths are the age of the parent at the time of
+	/// birth
+	pub fn write_newick<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		let children = self.children();
+		write!(out, "(")?;
+		for (i, root) in self.roots().iter().enumerate() {
+			if i > 0 {
+				write!(out, ",")?;
+			}
+			self.write_newick_node(out, &children, *root)?;
+		}
+		writeln!(out, ");")
+	}
+
+	/// Walks the tree with a stack rather than by recursion, as a lineage can
+	/// be thousands of generations deep
+	fn write_newick_node<W>(&self, out: &mut W, children: &BTreeMap<Id, Vec<Id>>, root: Id) -> io::Result<()>
+	where W: io::Write {
+		// each member with the index of the next child to write
+		let mut stack = vec![(root, 0)];
+		while let Some((id, next)) = stack.pop() {
+			let offspring = children.get(&id).map_or(&[][..], |c| &c[..]);
+			if next < offspring.len() {
+				write!(out, "{}", if next == 0 { "(" } else { "," })?;
+				stack.push((id, next + 1));
+				stack.push((offspring[next], 0));
+				continue;
+			}
+			if !offspring.is_empty() {
+				write!(out, ")")?;
+			}
+			let lineage = &self.members[&id].lineage;
+			let parent_birth_time =
+				lineage.parent.and_then(|p| self.members.get(&p)).map(|p| p.lineage.birth_time).unwrap_or(0.);
+			write!(out, "{}:{}", id, lineage.birth_time - parent_birth_time)?;
+		}
+		Ok(())
+	}
+
+	/// A directed graph with an edge from each parent and co-parent to their
+	/// offspring
+	pub fn write_graphml<W>(&self, out: &mut W) -> io::Result<()>
+	where W: io::Write {
+		writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+		writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+		writeln!(out, r#"  <key id="generation" for="node" attr.name="generation" attr.type="int"/>"#)?;
+		writeln!(out, r#"  <key id="birth_time" for="node" attr.name="birth_time" attr.type="double"/>"#)?;
+		writeln!(out, r#"  <key id="role" for="edge" attr.name="role" attr.type="string"/>"#)?;
+		writeln!(out, r#"  <graph id="lineage" edgedefault="directed">"#)?;
+		for (id, lineage) in self.iter() {
+			writeln!(
+				out,
+				r#"    <node id="n{}"><data key="generation">{}</data><data key="birth_time">{}</data></node>"#,
+				id, lineage.generation, lineage.birth_time
+			)?;
+		}
+		for (id, lineage) in self.iter() {
+			for (role, ancestor) in &[("parent", lineage.parent), ("co_parent", lineage.co_parent)] {
+				if let Some(ancestor) = ancestor.filter(|a| self.members.contains_key(a)) {
+					writeln!(
+						out,
+						r#"    <edge source="n{}" target="n{}"><data key="role">{}</data></edge>"#,
+						ancestor, id, role
+					)?;
+				}
+			}
+		}
+		writeln!(out, "  </graph>")?;
+		writeln!(out, "</graphml>")
+	}
+}
@@ -1,37 +1,53 @@
 pub mod agent;
 pub mod alert;
+pub mod boundary;
+pub mod decode;
+pub mod environment;
 pub mod gen;
+pub mod lineage;
 pub mod particle;
 pub mod persist;
 pub mod phen;
+pub mod scenario;
 pub mod segment;
+pub mod selection;
+pub mod species;
 pub mod swarm;
 
 use backend::obj;
 use backend::obj::*;
+use cgmath::InnerSpace;
 use chrono::DateTime;
 use chrono::Utc;
-use rand;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::f32::consts;
 use std::fs;
 use std::io;
-use std::io::Write;
+use std::mem;
 use std::path;
 
 use self::agent::Agent;
 use self::agent::AgentType;
 use self::agent::TypedAgent;
+use self::boundary::Boundary;
+use self::environment::Conditions;
+use self::lineage::{FamilyTree, Lineage};
 use self::particle::Particle;
+use self::scenario::SpawnRegion;
+use self::selection::Selection;
+use self::species::SpeciesMap;
 use self::swarm::*;
 use app::constants::*;
+use backend::config::SimulationConfig;
 use backend::messagebus::{Message, Outbox};
 use core::clock::*;
 use core::color::Rgba;
 use core::geometry::Transform;
 use core::geometry::*;
 use core::resource::ResourceLoader;
+use core::rng::{Seed, XorShiftRng};
 use serialize::base64::{self, ToBase64};
 
 pub use self::alert::Alert;
@@ -42,16 +58,29 @@ pub trait AgentState {
 
 pub struct World {
 	pub extent: Rect,
+	pub boundary: Boundary,
 	phase: Rgba,
-	swarms: HashMap<AgentType, Swarm>,
+	swarms: SwarmMap,
 	feeders: Vec<Feeder>,
-	registered: HashSet<Id>,
+	/// Changes whenever feeders are added or removed
+	feeders_revision: usize,
+	registered: BTreeSet<Id>,
 	registered_player_id: Option<Id>,
 	regenerations: usize,
 	minion_gene_pool: gen::GenePool,
 	resource_gene_pool: gen::GenePool,
+	/// The gene pool index each minion of the last regeneration was seeded from
+	founders: BTreeMap<Id, usize>,
+	/// Where founders are placed, if not around the centre
+	spawn_regions: Vec<SpawnRegion>,
 	clock: SimulationTimer,
 	particles: Vec<Particle>,
+	emitters: Vec<particle::Emitter>,
+	family_tree: FamilyTree,
+	species: SpeciesMap,
+	seed: Seed,
+	rng: XorShiftRng,
+	config: SimulationConfig,
 }
 
 impl AgentState for World {
@@ -61,17 +90,52 @@ impl AgentState for World {
 #[derive(Clone)]
 pub struct Feeder {
 	transform: Transform,
-	rate: Seconds,
+	/// Its own period, or none to follow the configured one
+	rate: Option<Seconds>,
 	intensity: f32,
+	power: f32,
+	orbit: bool,
+	/// Delay of its seasons, as a fraction of a year
+	phase: f32,
+	emitted: usize,
+	next_emission: Seconds,
 }
 
 impl Feeder {
-	pub fn new(x: f32, y: f32, rate: Seconds) -> Self {
-		Feeder { transform: Transform::from_position(Position::new(x, y)), rate, intensity: 1.0 }
+	/// A feeder which follows the configured period, given to time its first
+	/// emission
+	pub fn new(x: f32, y: f32, period: Seconds) -> Self {
+		Feeder {
+			transform: Transform::from_position(Position::new(x, y)),
+			rate: None,
+			intensity: 0.,
+			power: 1.,
+			orbit: true,
+			phase: 0.,
+			emitted: 0,
+			next_emission: period,
+		}
 	}
-	pub fn rate(&self) -> Seconds { self.rate }
+	/// Its own period, if it does not follow the configured one
+	pub fn rate(&self) -> Option<Seconds> { self.rate }
+	pub fn set_rate(&mut self, rate: Seconds) { self.rate = Some(rate) }
+	/// Time left before the next resource is emitted, when food is as
+	/// abundant as on average
+	pub fn next_emission(&self) -> Seconds { self.next_emission }
+	pub fn set_next_emission(&mut self, left: Seconds) { self.next_emission = left }
 	pub fn intensity(&self) -> f32 { self.intensity }
 	pub fn set_intensity(&mut self, intensity: f32) { self.intensity = intensity }
+	/// Scales both the light and the charge of the resources, 1 by default
+	pub fn power(&self) -> f32 { self.power }
+	pub fn set_power(&mut self, power: f32) { self.power = power }
+	/// Whether the feeder drifts around the centre of the world
+	pub fn is_orbiting(&self) -> bool { self.orbit }
+	pub fn set_orbiting(&mut self, orbit: bool) { self.orbit = orbit }
+	pub fn phase(&self) -> f32 { self.phase }
+	pub fn set_phase(&mut self, phase: f32) { self.phase = phase }
+	/// Total number of resources emitted so far
+	pub fn emitted(&self) -> usize { self.emitted }
+	pub fn count_emitted(&mut self, count: usize) { self.emitted += count }
 }
 
 impl Transformable for Feeder {
@@ -80,40 +144,61 @@ impl Transformable for Feeder {
 }
 
 impl World {
-	pub fn new<R>(res: &R, minion_gene_pool: &str) -> Self
+	pub fn new<R>(res: &R, minion_gene_pool: &str, seed: Seed) -> Self
 	where R: ResourceLoader<u8> {
-		let mut swarms = HashMap::new();
+		Self::with_config(res, minion_gene_pool, seed, SimulationConfig::default())
+	}
+
+	/// A new world, whose extent and feeders are laid out according to the
+	/// configuration
+	pub fn with_config<R>(res: &R, minion_gene_pool: &str, seed: Seed, config: SimulationConfig) -> Self
+	where R: ResourceLoader<u8> {
+		let mut swarms = BTreeMap::new();
 		let types = AgentType::all();
 		let clock = SimulationTimer::new();
 		for t in types {
 			swarms.insert(*t, Swarm::new(*t, phen::phenotype_of(*t)));
 		}
-		fn default_gene_pool(_: io::Error) -> gen::GenePool {
+		fn default_gene_pool(e: io::Error) -> gen::GenePool {
+			if e.kind() == io::ErrorKind::InvalidData {
+				warn!("Invalid gene pool, using the default one: {}", e);
+			}
 			gen::GenePool::parse_from_base64(DEFAULT_MINION_GENE_POOL)
 		}
-		let emitter_rate = Seconds::new(EMITTER_PERIOD);
-		let num_emitters: usize = 7;
+		let emitter_period = Seconds::new(config.emitter_period);
+		let num_emitters = config.emitter_count;
 		let feeders = (0..num_emitters)
 			.map(|i| {
 				let (s, c) = (consts::PI * 2. * (i as f32 / num_emitters as f32)).sin_cos();
-				Feeder::new(c * EMITTER_DISTANCE, s * EMITTER_DISTANCE, emitter_rate)
+				Feeder::new(c * config.emitter_distance, s * config.emitter_distance, emitter_period)
 			})
 			.collect::<Vec<_>>();
+		let radius = config.world_radius;
 		World {
-			extent: Rect::new(-WORLD_RADIUS, -WORLD_RADIUS, WORLD_RADIUS, WORLD_RADIUS),
+			extent: Rect::new(-radius, -radius, radius, radius),
+			boundary: config.boundary,
 			phase: COLOR_TRANSPARENT,
 			swarms,
 			feeders,
+			feeders_revision: 0,
 			minion_gene_pool: res
 				.load(minion_gene_pool)
-				.map(|data| gen::GenePool::parse_from_resource(&data))
+				.and_then(|data| gen::GenePool::parse_from_resource(&data))
 				.unwrap_or_else(default_gene_pool),
 			resource_gene_pool: gen::GenePool::parse_from_base64(DEFAULT_RESOURCE_GENE_POOL),
-			registered: HashSet::new(),
+			registered: BTreeSet::new(),
 			registered_player_id: None,
 			regenerations: 0usize,
+			founders: BTreeMap::new(),
+			spawn_regions: Vec::new(),
 			clock,
 			particles: Vec::with_capacity(10000),
+			emitters: Vec::new(),
+			family_tree: FamilyTree::default(),
+			species: SpeciesMap::default(),
+			seed,
+			rng: XorShiftRng::from_seed(seed),
+			config,
 		}
 	}
 
@@ -124,9 +209,17 @@ impl World {
 		self.registered.clear();
 		self.registered_player_id = None;
 		self.particles.clear();
+		self.emitters.clear();
+		self.family_tree.clear();
+		self.species.clear();
+		self.founders.clear();
 	}
 
-	pub fn tick(&mut self, dt: Seconds) { self.clock.tick(dt); }
+	pub fn tick(&mut self, dt: Seconds) {
+		self.clock.tick(dt);
+		// keeps the generators forked by the systems from repeating themselves
+		self.rng.next_u32();
+	}
 
 	pub fn seconds(&self) -> Seconds { self.clock.seconds() }
 
@@ -138,12 +231,29 @@ impl World {
 		}
 	}
 
+	pub fn seed(&self) -> Seed { self.seed }
+
+	pub fn reseed(&mut self, seed: Seed) {
+		self.seed = seed;
+		self.rng = XorShiftRng::from_seed(seed);
+	}
+
+	pub fn rng(&self) -> &XorShiftRng { &self.rng }
+
+	pub fn rng_mut(&mut self) -> &mut XorShiftRng { &mut self.rng }
+
+	pub fn fork_rng(&self) -> XorShiftRng { self.rng.fork() }
+
 	pub fn new_resource(&mut self, transform: Transform, motion: Motion) -> obj::Id {
-		let mut gen = &mut self.resource_gene_pool.next();
+		self.new_resource_with_charge(transform, motion, DEFAULT_RESOURCE_CHARGE)
+	}
+
+	pub fn new_resource_with_charge(&mut self, transform: Transform, motion: Motion, charge: f32) -> obj::Id {
+		let mut gen = &mut self.resource_gene_pool.next(&mut self.rng);
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Resource).spawn(
 			&mut gen,
-			agent::InitialState { transform, motion, charge: DEFAULT_RESOURCE_CHARGE, ..Default::default() },
+			agent::InitialState { transform, motion, charge, ..Default::default() },
 			&clock,
 		);
 		self.register(id)
@@ -161,60 +271,112 @@ impl World {
 		self.register(id)
 	}
 
-	pub fn new_spore(&mut self, outbox: &dyn Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
+	pub fn new_spore(&mut self, outbox: &dyn Outbox, transform: Transform, dna: &gen::Dna, parent: obj::Id) -> obj::Id {
 		let clock = self.clock.clone();
+		let mut genome = gen::Genome::copy_from(dna).mutate_with_rate(&mut self.rng, self.config.mutation_rate);
 		let id = self.swarm_mut(&AgentType::Spore).spawn(
-			&mut gen::Genome::copy_from(dna).mutate(&mut rand::thread_rng()),
+			&mut genome,
 			agent::InitialState { transform: transform.clone(), charge: DEFAULT_SPORE_CHARGE, ..Default::default() },
 			&clock,
 		);
+		let parent_lineage = self.agent(parent).map(|a| a.state.lineage().clone()).unwrap_or_default();
+		let lineage = Lineage::offspring_of(parent, &parent_lineage, self.seconds().get());
+		self.set_lineage(id, lineage);
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_new_spore(transform, livery_color, id)));
 		self.register(id)
 	}
 
-	pub fn hatch_spore(&mut self, outbox: &dyn Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
+	pub fn hatch_spore(
+		&mut self,
+		outbox: &dyn Outbox,
+		transform: Transform,
+		dna: &gen::Dna,
+		foreign_dna: &Option<gen::Dna>,
+		lineage: &Lineage,
+	) -> obj::Id {
 		let clock = self.clock.clone();
+		let mut genome = match *foreign_dna {
+			Some(ref foreign) => gen::Genome::copy_from(foreign).crossover(&mut self.rng, dna),
+			None => gen::Genome::copy_from(dna),
+		};
 		let id = self.swarm_mut(&AgentType::Minion).spawn(
-			&mut gen::Genome::copy_from(dna),
+			&mut genome,
 			agent::InitialState { transform: transform.clone(), charge: DEFAULT_MINION_CHARGE, ..Default::default() },
 			&clock,
 		);
+		let birth_time = self.seconds().get();
+		self.set_lineage(id, Lineage { birth_time, ..lineage.clone() });
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_new_minion(transform, livery_color)));
 		self.register(id)
 	}
 
 	pub fn randomize_minion(&mut self, pos: Position, motion: Motion) -> obj::Id {
-		self.minion_gene_pool.randomize();
+		self.minion_gene_pool.randomize(&mut self.rng);
 		self.new_minion(pos, motion)
 	}
 
+	pub fn minion_gene_pool(&self) -> &gen::GenePool { &self.minion_gene_pool }
+
+	pub fn set_minion_selection(&mut self, selection: Box<dyn Selection>) {
+		self.minion_gene_pool.set_selection(selection)
+	}
+
+	/// The fitness of each genome which seeded the last regeneration is the
+	/// number of descendants of the minions it seeded
+	fn update_gene_pool_fitness(&mut self) {
+		let mut fitness = BTreeMap::new();
+		for (id, index) in &self.founders {
+			*fitness.entry(*index).or_insert(0usize) += 1 + self.family_tree.descendants(*id);
+		}
+		for (index, fitness) in fitness {
+			self.minion_gene_pool.set_fitness(index, fitness as f32);
+		}
+		self.founders.clear();
+	}
+
 	pub fn init_minions(&mut self) {
 		self.regenerations += 1;
+		self.update_gene_pool_fitness();
 		let n = self.minion_gene_pool.len();
 		let clock = self.clock.clone();
 		let mut r = self.extent.top_right().x * INITIAL_SPAWN_RADIUS_RATIO;
 		let mut angle = 0.0f32;
 		let angle_delta = consts::PI * 2. / INITIAL_SPAWN_RADIUS_SLICES as f32;
-		for _ in 0..n {
-			let pos = Position::new(r * angle.cos(), r * angle.sin());
-			let mut gen = self.minion_gene_pool.next();
+		for i in 0..n {
+			let transform = if self.spawn_regions.is_empty() {
+				Transform::new(Position::new(r * angle.cos(), r * angle.sin()), angle + consts::PI / 2.)
+			} else {
+				let region = self.spawn_regions[i % self.spawn_regions.len()];
+				let pos = region.random_point(&mut self.rng);
+				Transform::new(pos, consts::PI * 2. * self.rng.next_f32())
+			};
+			let (index, mut gen) = self.minion_gene_pool.select(&mut self.rng);
 			let id = self.swarm_mut(&AgentType::Minion).spawn(
 				&mut gen,
-				agent::InitialState {
-					transform: Transform::new(pos, angle + consts::PI / 2.),
-					charge: DEFAULT_MINION_CHARGE,
-					..Default::default()
-				},
+				agent::InitialState { transform, charge: DEFAULT_MINION_CHARGE, ..Default::default() },
 				&clock,
 			);
+			self.set_lineage(id, Lineage::founder(clock.seconds().get()));
+			self.founders.insert(id, index);
 			self.register(id);
 			angle += angle_delta;
 			r += INITIAL_SPAWN_RADIUS_INCREMENT;
 		}
 	}
 
+	/// A static box, centred on the position of the transform
+	pub fn new_obstacle(&mut self, transform: Transform, width: f32, height: f32) -> obj::Id {
+		let clock = self.clock.clone();
+		let id = self.swarm_mut(&AgentType::Prop).spawn(
+			&mut gen::Genome::copy_from(&phen::prop_dna(width, height)),
+			agent::InitialState { transform, ..Default::default() },
+			&clock,
+		);
+		self.register(id)
+	}
+
 	pub fn init_players(&mut self) {
 		self.registered_player_id = Some(self.spawn_player(Position::new(0., 0.), Motion::default()))
 	}
@@ -280,22 +442,97 @@ impl World {
 
 	pub fn new_minion(&mut self, pos: Position, motion: Motion) -> obj::Id {
 		let angle = consts::PI / 2. + f32::atan2(pos.y, pos.x);
-		let mut gen = self.minion_gene_pool.next();
+		let mut gen = self.minion_gene_pool.next(&mut self.rng);
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Minion).spawn(
 			&mut gen,
 			agent::InitialState { transform: Transform::new(pos, angle), motion, charge: 0.3, ..Default::default() },
 			&clock,
 		);
+		self.set_lineage(id, Lineage::founder(clock.seconds().get()));
 		self.register(id)
 	}
 
+	/// Minions also become members of the family tree
+	pub fn set_lineage(&mut self, id: obj::Id, lineage: Lineage) {
+		if id.type_of() == AgentType::Minion {
+			self.family_tree.insert(id, lineage.clone());
+		}
+		if let Some(agent) = self.agent_mut(id) {
+			agent.state.set_lineage(lineage);
+		}
+	}
+
+	pub fn family_tree(&self) -> &FamilyTree { &self.family_tree }
+
+	pub fn species(&self) -> &SpeciesMap { &self.species }
+
+	pub fn species_mut(&mut self) -> &mut SpeciesMap { &mut self.species }
+
+	/// Parameters which only affect new worlds, such as the extent, are
+	/// ignored. Materials are updated on every agent, but only take effect on
+	/// those not yet picked up by the physics. Feeders with no period of their
+	/// own follow the new one
+	pub fn set_config(&mut self, config: SimulationConfig) {
+		self.minion_gene_pool.set_mutation_rate(config.mutation_rate);
+		self.resource_gene_pool.set_mutation_rate(config.mutation_rate);
+		self.config = config;
+		let ids = self.swarms.values().flat_map(|swarm| swarm.agents().keys().cloned()).collect::<Vec<_>>();
+		for id in ids {
+			self.apply_materials(id);
+		}
+	}
+
+	pub fn config(&self) -> &SimulationConfig { &self.config }
+
+	/// The state of the environment now, for the world as a whole
+	pub fn conditions(&self) -> Conditions { self.config.environment.world_conditions(self.seconds().get()) }
+
+	/// Seconds between two resources dropped by a feeder
+	pub fn feeder_period(&self, index: usize) -> Seconds {
+		self.feeders.get(index).and_then(Feeder::rate).unwrap_or_else(|| Seconds::new(self.config.emitter_period))
+	}
+
+	/// The state of the environment now, around a feeder
+	pub fn feeder_conditions(&self, index: usize) -> Conditions {
+		let phase = self.feeders.get(index).map(Feeder::phase).unwrap_or(0.);
+		self.config.environment.conditions(self.seconds().get(), index, phase)
+	}
+
+	fn apply_materials(&mut self, id: obj::Id) {
+		let config = self.config.clone();
+		if let Some(agent) = self.agent_mut(id) {
+			let density = match id.type_of() {
+				AgentType::Minion => Some(config.density_minion),
+				AgentType::Resource => Some(config.density_resource),
+				AgentType::Spore => Some(config.density_spore),
+				AgentType::Player => Some(config.density_player),
+				_ => None,
+			};
+			let is_player = id.type_of() == AgentType::Player;
+			for segment in agent.segments_mut() {
+				if let Some(density) = density {
+					segment.material.density = density;
+				}
+				if !is_player {
+					segment.material.linear_damping = config.linear_damping;
+				}
+				segment.material.angular_damping = config.angular_damping;
+			}
+		}
+	}
+
+	/// Queues a new agent for the systems to pick up. Its materials are those
+	/// of the configuration
 	pub fn register(&mut self, id: obj::Id) -> obj::Id {
+		self.apply_materials(id);
 		self.registered.insert(id);
 		id
 	}
 
-	pub fn registered(&mut self) -> Box<[Id]> { self.registered.drain().collect::<Vec<_>>().into_boxed_slice() }
+	pub fn registered(&mut self) -> Box<[Id]> {
+		mem::take(&mut self.registered).into_iter().collect::<Vec<_>>().into_boxed_slice()
+	}
 
 	#[allow(dead_code)]
 	pub fn agent(&self, id: obj::Id) -> Option<&Agent> { self.swarms.get(&id.type_of()).and_then(|m| m.get(id)) }
@@ -325,6 +562,45 @@ impl World {
 
 	pub fn feeders_mut(&mut self) -> &mut [Feeder] { self.feeders.as_mut_slice() }
 
+	pub fn feeders_revision(&self) -> usize { self.feeders_revision }
+
+	/// Replaces all the feeders, which the systems pick up from scratch
+	pub fn set_feeders(&mut self, feeders: Vec<Feeder>) {
+		self.feeders = feeders;
+		self.feeders_revision += 1;
+	}
+
+	/// Index of the feeder nearest to a point, if within reach
+	pub fn feeder_at(&self, pos: Position) -> Option<usize> {
+		self.feeders
+			.iter()
+			.map(|f| (f.transform.position - pos).magnitude2())
+			.enumerate()
+			.filter(|(_, d2)| *d2 <= EMITTER_PICK_RADIUS * EMITTER_PICK_RADIUS)
+			.min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal))
+			.map(|(i, _)| i)
+	}
+
+	/// A new feeder with the configured period, which stays put
+	pub fn add_feeder(&mut self, pos: Position) -> usize {
+		let mut feeder = Feeder::new(pos.x, pos.y, Seconds::new(self.config.emitter_period));
+		feeder.orbit = false;
+		self.feeders.push(feeder);
+		self.feeders_revision += 1;
+		self.feeders.len() - 1
+	}
+
+	pub fn remove_feeder(&mut self, index: usize) -> Option<Feeder> {
+		if index < self.feeders.len() {
+			self.feeders_revision += 1;
+			Some(self.feeders.remove(index))
+		} else {
+			None
+		}
+	}
+
+	pub fn spawn_regions(&self) -> &[SpawnRegion] { self.spawn_regions.as_slice() }
+
 	pub fn swarms(&self) -> &SwarmMap { &self.swarms }
 
 	pub fn phase(&self) -> Rgba { self.phase }
@@ -337,6 +613,11 @@ impl World {
 
 	pub fn add_particle(&mut self, particle: Particle) { self.particles.push(particle); }
 
+	/// The particle emitters alive at the end of the last step
+	pub fn emitters(&self) -> &[particle::Emitter] { &self.emitters }
+
+	pub fn set_emitters(&mut self, emitters: Vec<particle::Emitter>) { self.emitters = emitters }
+
 	pub fn cleanup_before(&mut self) { self.clear_particles(); }
 
 	pub fn sweep(&mut self) -> Box<[Agent]> {
@@ -344,30 +625,61 @@ impl World {
 		for swarm in self.swarms.values_mut() {
 			swarm.free_resources(&mut v);
 		}
+		if self.family_tree.is_overgrown() {
+			self.prune_family_tree();
+		}
 		v.into_boxed_slice()
 	}
 
-	pub fn serialize(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+	/// Forgets the dead minions with no living descendants. The founders of the
+	/// last regeneration are kept for the fitness of their genomes, and the
+	/// parents of the spores for the minions about to hatch
+	fn prune_family_tree(&mut self) {
+		let mut alive = self.founders.keys().cloned().collect::<BTreeSet<_>>();
+		alive.extend(self.agents(AgentType::Minion).keys());
+		for spore in self.agents(AgentType::Spore).values() {
+			alive.extend(spore.state.lineage().parent);
+			alive.extend(spore.state.lineage().co_parent);
+		}
+		self.family_tree.prune(&alive);
+	}
+
+	pub fn serialize(&self, containing_dir: &path::Path, format: persist::SnapshotFormat) -> io::Result<path::PathBuf> {
 		let now: DateTime<Utc> = Utc::now();
 		if fs::create_dir_all(containing_dir).is_err() {
 			debug!("Did not create {} (existing?)", containing_dir.display());
 		}
-		let file_name = containing_dir.join(now.format(DUMP_FILE_PATTERN_JSON).to_string());
+		let file_name = containing_dir.join(now.format(format.file_pattern()).to_string());
 		persist::Serializer::save(file_name.as_path(), self)?;
 		Ok(file_name)
 	}
 
-	pub fn dump(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+	/// Saves the genomes of the living minions as a gene pool file, with their
+	/// generation, offspring count and species, and the snapshot the world was
+	/// restored from, if any
+	pub fn dump(&self, containing_dir: &path::Path, origin: Option<&path::Path>) -> io::Result<path::PathBuf> {
 		let now: DateTime<Utc> = Utc::now();
 		let file_name = containing_dir.join(now.format(DUMP_FILE_PATTERN_CSV).to_string());
 		if fs::create_dir_all(containing_dir).is_err() {
 			debug!("Did not create {} (existing?)", containing_dir.display());
 		}
-		let mut f = fs::File::create(&file_name)?;
-		for (_, agent) in self.agents(agent::AgentType::Minion).iter() {
+		let origin = origin.and_then(|p| p.file_name()).map(|p| p.to_string_lossy().into_owned());
+		let minions = self.agents(agent::AgentType::Minion);
+		let metadata = minions
+			.values()
+			.map(|agent| gen::GeneMetadata {
+				origin: origin.clone(),
+				generation: self.family_tree.get(agent.id()).map(|l| l.generation),
+				offspring: Some(self.family_tree.offspring(agent.id())),
+				species: self.species.species_of(agent.id()),
+				..Default::default()
+			})
+			.collect::<Vec<_>>();
+		for agent in minions.values() {
 			info!("{}", agent.dna().to_base64(base64::STANDARD));
-			f.write_fmt(format_args!("{}\n", agent.dna().to_base64(base64::STANDARD)))?;
 		}
+		let f = io::BufWriter::new(fs::File::create(&file_name)?);
+		gen::write_gene_pool(f, minions.values().map(|agent| &agent.dna()[..]).zip(metadata.iter()))?;
 		Ok(file_name)
 	}
 }
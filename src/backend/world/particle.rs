@@ -14,7 +14,7 @@ pub enum Fader {
 }
 
 #[allow(unused)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum EmitterAttachment {
 	None,
 	Agent(obj::Id),
@@ -26,7 +26,7 @@ impl Default for EmitterAttachment {
 	fn default() -> EmitterAttachment { EmitterAttachment::None }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum EmitterStyle {
 	Explosion { cluster_size: u8, color: Rgba<f32> },
 	Ping { color: Rgba<f32> },
@@ -57,10 +57,10 @@ impl EmitterStyle {
 	}
 }
 
-#[allow(unused)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Particle {
 	transform: Transform,
-	direction: Velocity,
+	velocity: Velocity,
 	tag: isize,
 	trail: Box<[Position]>,
 	faders: [f32; 4],
@@ -69,13 +69,16 @@ pub struct Particle {
 	age: Seconds,
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Emitter {
 	pub id: Option<obj::Id>,
 	pub transform: Transform,
 	pub motion: Motion,
 	pub attached_to: EmitterAttachment,
 	pub style: EmitterStyle,
+	/// Overrides the time to live of the style, for emitters restored from a
+	/// snapshot
+	pub ttl: Option<Seconds>,
 }
 
 impl Emitter {
@@ -99,7 +102,7 @@ impl Particle {
 	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		transform: Transform,
-		direction: Velocity,
+		velocity: Velocity,
 		tag: isize,
 		trail: Box<[Position]>,
 		faders: [f32; 4],
@@ -107,11 +110,23 @@ impl Particle {
 		effect: (Rgba<f32>, Rgba<f32>),
 		age: Seconds,
 	) -> Particle {
-		Particle { transform, direction, tag, trail, faders, color, effect, age }
+		Particle { transform, velocity, tag, trail, faders, color, effect, age }
 	}
 
 	pub fn transform(&self) -> Transform { self.transform.clone() }
 
+	pub fn velocity(&self) -> Velocity { self.velocity }
+
+	pub fn tag(&self) -> isize { self.tag }
+
+	pub fn faders(&self) -> [f32; 4] { self.faders }
+
+	pub fn colors(&self) -> (Rgba<f32>, Rgba<f32>) { self.color }
+
+	pub fn effects(&self) -> (Rgba<f32>, Rgba<f32>) { self.effect }
+
+	pub fn age(&self) -> Seconds { self.age }
+
 	pub fn trail(&self) -> &[Position] { &self.trail }
 
 	pub fn scale(&self) -> f32 { self.faders[Fader::Scale as usize] }
@@ -1,19 +1,49 @@
+use app::constants::*;
+use backend::config::SimulationConfig;
+use backend::inspect;
+use backend::obj::Id;
+use backend::stats::Distribution;
 use backend::world;
 use backend::world::agent;
+use backend::world::agent::TypedAgent;
+use backend::world::boundary::Boundary;
 use backend::world::gen;
+use backend::world::lineage::{FamilyTree, Lineage};
+use backend::world::particle;
+use backend::world::scenario::SpawnRegion;
+use backend::world::segment;
+use backend::world::species::{SpeciesId, SpeciesMap};
 use core::clock;
 use core::geometry;
+use core::rng;
+use flate2;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use num_traits::FromPrimitive;
+use serde_cbor;
 use serde_json;
+use serde_json::Value;
 use serialize::base64::{self, FromBase64, ToBase64};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 use std::fs;
 use std::io;
+use std::io::Read;
 use std::path;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Segment {
 	charge: f32,
 	target_charge: f32,
+	#[serde(default)]
+	intent: Option<segment::Intent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Hourglass {
+	capacity: clock::SecondsValue,
+	left: clock::SecondsValue,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -33,6 +63,47 @@ struct Agent {
 	phase: f32,
 	energy: f32,
 	segments: Vec<Segment>,
+	#[serde(default)]
+	lineage: Lineage,
+	#[serde(default)]
+	lifecycle: Option<Hourglass>,
+	#[serde(default)]
+	foreign_dna: Option<String>,
+	#[serde(default)]
+	target: Option<usize>,
+	#[serde(default)]
+	target_position: Option<geometry::Position>,
+	/// Most recent position first
+	#[serde(default)]
+	trajectory: Vec<geometry::Position>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Feeder {
+	x: f32,
+	y: f32,
+	angle: f32,
+	/// None when the feeder follows the configured period
+	rate: Option<clock::SecondsValue>,
+	intensity: f32,
+	#[serde(default = "default_feeder_power")]
+	power: f32,
+	#[serde(default = "default_feeder_orbit")]
+	orbit: bool,
+	#[serde(default)]
+	phase: f32,
+	emitted: usize,
+	next_emission: clock::SecondsValue,
+}
+
+fn default_feeder_power() -> f32 { 1. }
+
+fn default_feeder_orbit() -> bool { true }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Species {
+	id: SpeciesId,
+	representative: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -42,33 +113,281 @@ pub struct Swarm {
 	agents: Vec<Agent>,
 }
 
+/// Version of the snapshot schema written by this build. Snapshots with no
+/// version are version 1. It goes up with every change to the schema, so that
+/// older builds refuse the snapshots they would only read in part
+///
+/// 2. seed, lineage, family tree and species
+/// 3. clock, lifecycles, targets, intents, feeders and particles
+/// 4. configuration, spawn regions, boundary and feeder power, orbit and phase
+/// 5. family tree as a list of members
+/// 6. offspring and descendants of the family tree members, which may be pruned
+/// 7. feeders following the configured period have none of their own
+pub const SNAPSHOT_VERSION: u32 = 7;
+
+type Migration = fn(&mut Value) -> io::Result<()>;
+
+/// Migrations of the snapshots older than the version they are listed with.
+/// Fields added with a default need none
+const MIGRATIONS: &[(u32, Migration)] = &[(5, migrate_v4_to_v5), (7, migrate_v6_to_v7)];
+
+/// The members of the family tree were a map by id, which CBOR wrote with
+/// integer keys
+fn migrate_v4_to_v5(value: &mut Value) -> io::Result<()> {
+	let members = match value.pointer_mut("/family_tree/members").map(Value::take) {
+		Some(Value::Object(members)) => members,
+		_ => return Ok(()),
+	};
+	let members = members
+		.into_iter()
+		.map(|(id, mut lineage)| {
+			let id = id.parse::<Id>().map_err(|_| invalid_data(format!("Invalid family tree member {}", id)))?;
+			if let Value::Object(ref mut fields) = lineage {
+				fields.insert("id".to_owned(), id.into());
+			}
+			Ok(lineage)
+		})
+		.collect::<io::Result<Vec<_>>>()?;
+	value["family_tree"] = Value::Array(members);
+	Ok(())
+}
+
+/// Every feeder had a period. Those at the configured one followed it when it
+/// was reloaded
+fn migrate_v6_to_v7(value: &mut Value) -> io::Result<()> {
+	let period = value.pointer("/config/emitter_period").and_then(Value::as_f64).unwrap_or(EMITTER_PERIOD);
+	if let Some(Value::Array(feeders)) = value.get_mut("feeders") {
+		for feeder in feeders.iter_mut().filter(|f| f["rate"].as_f64() == Some(period)) {
+			feeder["rate"] = Value::Null;
+		}
+	}
+	Ok(())
+}
+
+fn invalid_data(message: String) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, message) }
+
+fn cbor_error(e: serde_cbor::Error) -> io::Error { invalid_data(format!("Invalid CBOR snapshot: {}", e)) }
+
+/// Snapshots are written as pretty printed JSON, or as CBOR, a compact binary
+/// encoding of the same model, optionally compressed with gzip
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SnapshotFormat {
+	#[default]
+	Json,
+	Cbor,
+	CompressedCbor,
+}
+
+impl SnapshotFormat {
+	pub fn all() -> &'static [SnapshotFormat] {
+		&[SnapshotFormat::Json, SnapshotFormat::Cbor, SnapshotFormat::CompressedCbor]
+	}
+
+	/// CBOR for .cbor files, compressed CBOR for .cbor.gz files, JSON otherwise
+	pub fn from_path(path: &path::Path) -> Self {
+		let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+		Self::all()
+			.iter()
+			.cloned()
+			.find(|f| *f != SnapshotFormat::Json && file_name.ends_with(&format!(".{}", f.extension())))
+			.unwrap_or(SnapshotFormat::Json)
+	}
+
+	/// Parses a format by its file extension
+	pub fn from_extension(extension: &str) -> Option<Self> {
+		Self::all().iter().cloned().find(|f| f.extension() == extension)
+	}
+
+	/// Whether the file name has the extension of any supported format
+	pub fn is_snapshot(path: &path::Path) -> bool {
+		let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+		Self::all().iter().any(|f| file_name.ends_with(&format!(".{}", f.extension())))
+	}
+
+	/// Guesses the format from the first bytes of a snapshot
+	pub fn detect(bytes: &[u8]) -> Self {
+		const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+		if bytes.starts_with(GZIP_MAGIC) {
+			SnapshotFormat::CompressedCbor
+		} else if bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+			SnapshotFormat::Json
+		} else {
+			SnapshotFormat::Cbor
+		}
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			SnapshotFormat::Json => "json",
+			SnapshotFormat::Cbor => "cbor",
+			SnapshotFormat::CompressedCbor => "cbor.gz",
+		}
+	}
+
+	pub fn file_pattern(self) -> &'static str {
+		match self {
+			SnapshotFormat::Json => DUMP_FILE_PATTERN_JSON,
+			SnapshotFormat::Cbor => DUMP_FILE_PATTERN_CBOR,
+			SnapshotFormat::CompressedCbor => DUMP_FILE_PATTERN_CBOR_GZ,
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct World {
+	version: u32,
 	left: f32,
 	bottom: f32,
 	right: f32,
 	top: f32,
+	/// Snapshots without a boundary kill the minions which leave the extent
+	#[serde(default)]
+	boundary: Option<Boundary>,
 	swarms: Vec<Swarm>,
 	regenerations: usize,
 	minion_gene_pool: Vec<String>,
 	minion_gene_pool_index: usize,
 	resource_gene_pool: Vec<String>,
 	resource_gene_pool_index: usize,
+	#[serde(default)]
+	seed: Option<rng::Seed>,
+	#[serde(default)]
+	rng_state: Option<rng::RngState>,
+	#[serde(default)]
+	family_tree: FamilyTree,
+	#[serde(default)]
+	species: Vec<Species>,
+	#[serde(default)]
+	next_species_id: Option<SpeciesId>,
+	#[serde(default)]
+	clock: clock::SecondsValue,
+	/// Snapshots without feeders keep the ones of the world they are restored
+	/// into
+	#[serde(default)]
+	feeders: Option<Vec<Feeder>>,
+	#[serde(default)]
+	particles: Vec<particle::Particle>,
+	#[serde(default)]
+	emitters: Vec<particle::Emitter>,
+	/// Snapshots without a configuration keep the one of the world they are
+	/// restored into
+	#[serde(default)]
+	config: Option<SimulationConfig>,
+	#[serde(default)]
+	spawn_regions: Vec<SpawnRegion>,
+}
+
+impl World {
+	/// Summary of the snapshot, listing at most `top` genomes, species and
+	/// agents
+	pub fn report(&self, top: usize) -> inspect::Report {
+		let agent_type = |swarm: &Swarm| {
+			agent::AgentType::from_usize(swarm.agent_type)
+				.map(|t| t.to_string())
+				.unwrap_or_else(|| "Unknown".to_owned())
+		};
+		let mut counts: BTreeMap<String, usize> = agent::AgentType::all().iter().map(|t| (t.to_string(), 0)).collect();
+		for swarm in &self.swarms {
+			*counts.entry(agent_type(swarm)).or_insert(0) += swarm.agents.len();
+		}
+		let minions = self
+			.swarms
+			.iter()
+			.filter(|s| s.agent_type == agent::AgentType::Minion as usize)
+			.flat_map(|s| s.agents.iter())
+			.collect::<Vec<_>>();
+
+		let mut genome_counts: BTreeMap<&str, usize> = BTreeMap::new();
+		for minion in &minions {
+			*genome_counts.entry(minion.dna.as_str()).or_insert(0) += 1;
+		}
+		let mut common_genomes = genome_counts
+			.into_iter()
+			.map(|(dna, count)| inspect::GenomeCount { dna: dna.to_owned(), count })
+			.collect::<Vec<_>>();
+		common_genomes.sort_by_key(|g| cmp::Reverse(g.count));
+		common_genomes.truncate(top);
+
+		let mut species = SpeciesMap::default();
+		species.restore(
+			self.next_species_id.unwrap_or(1),
+			self.species
+				.iter()
+				.filter_map(|s| s.representative.from_base64().ok().map(|dna| (s.id, dna.into_boxed_slice())))
+				.collect(),
+		);
+		species.cluster(
+			&minions
+				.iter()
+				.filter_map(|a| a.dna.from_base64().ok().map(|dna| (a.id, dna.into_boxed_slice())))
+				.collect::<Vec<_>>(),
+		);
+		let mut species_summaries = species
+			.iter()
+			.map(|s| inspect::SpeciesSummary {
+				id: s.id(),
+				representative: s.representative().to_base64(base64::STANDARD),
+				members: s.size(),
+			})
+			.collect::<Vec<_>>();
+		species_summaries.sort_by_key(|s| cmp::Reverse(s.members));
+		species_summaries.truncate(top);
+
+		let mut oldest = self
+			.swarms
+			.iter()
+			.flat_map(|swarm| {
+				swarm.agents.iter().map(move |a| inspect::AgentSummary {
+					id: a.id,
+					agent_type: agent_type(swarm),
+					age: a.age_seconds,
+					generation: a.lineage.generation,
+					energy: a.energy,
+					dna: a.dna.clone(),
+				})
+			})
+			.collect::<Vec<_>>();
+		oldest.sort_by(|a, b| b.age.partial_cmp(&a.age).unwrap_or(cmp::Ordering::Equal));
+		oldest.truncate(top);
+
+		inspect::Report {
+			version: self.version,
+			seed: self.seed,
+			time: self.clock,
+			config: self.config.clone(),
+			extent: inspect::Extent { left: self.left, bottom: self.bottom, right: self.right, top: self.top },
+			counts,
+			minion_gene_pool: inspect::GenePool {
+				size: self.minion_gene_pool.len(),
+				index: self.minion_gene_pool_index,
+			},
+			resource_gene_pool: inspect::GenePool {
+				size: self.resource_gene_pool.len(),
+				index: self.resource_gene_pool_index,
+			},
+			minion_energy: Distribution::of(minions.iter().map(|a| a.energy).collect()),
+			common_genomes,
+			species_count: species.len(),
+			species: species_summaries,
+			oldest,
+		}
+	}
 }
 
 pub struct Serializer;
 
 impl Serializer {
 	pub fn save_snapshot(world: &world::World) -> World {
-		fn serialize_swarm(src: &world::swarm::Swarm) -> Swarm {
+		fn serialize_swarm(src: &world::swarm::Swarm, timer: &clock::SimulationTimer) -> Swarm {
 			Swarm {
 				seq: src.seq() as usize,
 				agent_type: src.agent_type() as usize,
-				agents: src.agents().iter().map(|(_k, v)| serialize_agent(v)).collect(),
+				agents: src.agents().values().map(|v| serialize_agent(v, timer)).collect(),
 			}
 		}
 
-		fn serialize_agent(src: &world::agent::Agent) -> Agent {
+		fn serialize_agent(src: &world::agent::Agent, timer: &clock::SimulationTimer) -> Agent {
+			let lifecycle = src.state.lifecycle();
 			let body = &src.segments[0];
 
 			Agent {
@@ -87,42 +406,122 @@ impl Serializer {
 				phase: src.state.phase(),
 				energy: src.state.energy(),
 				segments: src.segments().iter().map(|s| serialize_segment(s)).collect(),
+				lineage: src.state.lineage().clone(),
+				lifecycle: Some(Hourglass { capacity: lifecycle.capacity().get(), left: lifecycle.left(timer).get() }),
+				foreign_dna: src.state.foreign_dna().as_ref().map(|dna| dna.to_base64(base64::STANDARD)),
+				target: *src.state.target(),
+				target_position: Some(src.state.target_position()),
+				trajectory: src.state.trajectory().to_vec(),
 			}
 		}
 
 		fn serialize_segment(src: &world::segment::Segment) -> Segment {
-			Segment { charge: src.state.charge(), target_charge: src.state.target_charge() }
+			Segment {
+				charge: src.state.charge(),
+				target_charge: src.state.target_charge(),
+				intent: Some(src.state.intent.clone()),
+			}
+		}
+
+		fn serialize_feeder(src: &world::Feeder) -> Feeder {
+			Feeder {
+				x: src.transform.position.x,
+				y: src.transform.position.y,
+				angle: src.transform.angle,
+				rate: src.rate.map(clock::Seconds::get),
+				intensity: src.intensity,
+				power: src.power,
+				orbit: src.orbit,
+				phase: src.phase,
+				emitted: src.emitted,
+				next_emission: src.next_emission.get(),
+			}
 		}
 
-		let swarms = world.swarms().iter().map(|(_k, v)| serialize_swarm(v)).collect();
+		let swarms = world.swarms().values().map(|v| serialize_swarm(v, &world.clock)).collect();
 		let minion_gene_pool: Vec<_> =
 			world.minion_gene_pool.gene_pool_iter().map(|dna| dna.to_base64(base64::STANDARD)).collect();
 		let resource_gene_pool: Vec<_> =
 			world.resource_gene_pool.gene_pool_iter().map(|dna| dna.to_base64(base64::STANDARD)).collect();
 		World {
+			version: SNAPSHOT_VERSION,
 			left: world.extent.min.x,
 			bottom: world.extent.min.y,
 			right: world.extent.max.x,
 			top: world.extent.max.y,
+			boundary: Some(world.boundary),
 			swarms,
 			regenerations: world.regenerations,
 			minion_gene_pool,
 			minion_gene_pool_index: world.minion_gene_pool.gene_pool_index(),
 			resource_gene_pool,
 			resource_gene_pool_index: world.resource_gene_pool.gene_pool_index(),
+			seed: Some(world.seed),
+			rng_state: Some(world.rng.state()),
+			family_tree: world.family_tree().clone(),
+			species: world
+				.species()
+				.iter()
+				.map(|s| Species { id: s.id(), representative: s.representative().to_base64(base64::STANDARD) })
+				.collect(),
+			next_species_id: Some(world.species().next_id()),
+			clock: world.seconds().get(),
+			feeders: Some(world.feeders().iter().map(serialize_feeder).collect()),
+			particles: world.particles().to_vec(),
+			emitters: world.emitters().to_vec(),
+			config: Some(world.config().clone()),
+			spawn_regions: world.spawn_regions.clone(),
 		}
 	}
 
 	pub fn restore_snapshot(src: &World, world: &mut world::World) {
+		world.clock = clock::SimulationTimer::from(clock::seconds(src.clock));
 		let timer = world.clock.clone();
 		world.extent.min.x = src.left;
 		world.extent.min.y = src.bottom;
 		world.extent.max.x = src.right;
 		world.extent.max.y = src.top;
+		world.boundary = src.boundary.unwrap_or_default();
 		world.regenerations = src.regenerations;
+		if let Some(ref config) = src.config {
+			world.config = config.clone();
+		}
+		if let Some(seed) = src.seed {
+			world.seed = seed;
+		}
+		if let Some(rng_state) = src.rng_state {
+			world.rng = rng::XorShiftRng::from_state(rng_state);
+		}
 
 		world.minion_gene_pool.populate_from_base64(&src.minion_gene_pool, src.minion_gene_pool_index);
 		world.resource_gene_pool.populate_from_base64(&src.resource_gene_pool, src.resource_gene_pool_index);
+		world.family_tree = src.family_tree.clone();
+		let species = src
+			.species
+			.iter()
+			.filter_map(|s| s.representative.from_base64().ok().map(|dna| (s.id, dna.into_boxed_slice())))
+			.collect::<Vec<_>>();
+		let next_species_id = species.iter().map(|s| s.0 + 1).max().unwrap_or(1).max(src.next_species_id.unwrap_or(1));
+		world.species.restore(next_species_id, species);
+		if let Some(ref feeders) = src.feeders {
+			let feeders = feeders
+				.iter()
+				.map(|f| world::Feeder {
+					transform: geometry::Transform::from_components(f.x, f.y, f.angle),
+					rate: f.rate.map(clock::seconds),
+					intensity: f.intensity,
+					power: f.power,
+					orbit: f.orbit,
+					phase: f.phase,
+					emitted: f.emitted,
+					next_emission: clock::seconds(f.next_emission),
+				})
+				.collect();
+			world.set_feeders(feeders);
+		}
+		world.spawn_regions = src.spawn_regions.clone();
+		world.particles = src.particles.clone();
+		world.emitters = src.emitters.clone();
 
 		let mut registered = Vec::new();
 		for src_swarm in &src.swarms {
@@ -150,11 +549,33 @@ impl Serializer {
 						);
 						if let Some(agent) = swarm.get_mut(id) {
 							agent.state.restore(src_agent.flags, src_agent.phase, src_agent.energy);
+							agent.state.set_lineage(src_agent.lineage.clone());
+							if let Some(ref lifecycle) = src_agent.lifecycle {
+								agent.state.set_lifecycle(clock::Hourglass::resume(
+									clock::seconds(lifecycle.capacity),
+									clock::seconds(lifecycle.left),
+									&timer,
+								));
+							}
+							agent.state.set_foreign_dna(
+								src_agent
+									.foreign_dna
+									.as_ref()
+									.and_then(|dna| dna.from_base64().ok())
+									.map(Vec::into_boxed_slice),
+							);
+							if let Some(target_position) = src_agent.target_position {
+								agent.state.retarget(src_agent.target, target_position);
+							}
+							agent.state.restore_trajectory(&src_agent.trajectory);
 
 							for (src_segment, dest_segment) in
 								src_agent.segments.iter().zip(agent.segments_mut().iter_mut())
 							{
 								dest_segment.state.restore(src_segment.charge, src_segment.target_charge);
+								if let Some(ref intent) = src_segment.intent {
+									dest_segment.state.intent = intent.clone();
+								}
 							}
 							registered.push(id);
 						}
@@ -167,33 +588,95 @@ impl Serializer {
 			.get(&agent::AgentType::Player)
 			.and_then(|swarm| swarm.agents().iter().next().map(|(k, _s)| *k));
 		for id in registered {
+			// snapshots without a family tree start one from the living minions
+			if id.type_of() == agent::AgentType::Minion && !world.family_tree.contains(id) {
+				let lineage = world.agent(id).map(|a| a.state.lineage().clone()).unwrap_or_default();
+				world.family_tree.insert(id, lineage);
+			}
 			world.register(id);
 		}
 	}
 
-	#[allow(unused)]
-	pub fn from_string(source: &str, dest: &mut world::World) -> Result<(), serde_json::Error> {
-		let result: Result<World, _> = serde_json::from_str(source);
-		match result {
-			Ok(src) => {
-				Self::restore_snapshot(&src, dest);
-				Ok(())
-			}
-			Err(e) => Err(e),
+	/// Brings a snapshot of any supported version up to the current one
+	pub fn migrate(mut value: Value) -> io::Result<World> {
+		let version = match value.get("version") {
+			None => 1,
+			Some(version) => version
+				.as_u64()
+				.filter(|v| *v >= 1)
+				.ok_or_else(|| invalid_data(format!("Invalid snapshot version {}", version)))?,
+		} as usize;
+		if version > SNAPSHOT_VERSION as usize {
+			return Err(invalid_data(format!(
+				"Snapshot version {} is newer than the latest supported version {}",
+				version, SNAPSHOT_VERSION
+			)));
 		}
+		for (_, migration) in MIGRATIONS.iter().filter(|(target, _)| *target as usize > version) {
+			migration(&mut value)?;
+		}
+		if let Value::Object(ref mut fields) = value {
+			fields.insert("version".to_owned(), SNAPSHOT_VERSION.into());
+		}
+		Ok(serde_json::from_value(value)?)
 	}
 
-	pub fn save(file_path: &path::Path, world: &world::World) -> io::Result<()> {
-		let out_file = fs::File::create(file_path)?;
-		let s_world = Self::save_snapshot(world);
-		serde_json::to_writer_pretty(out_file, &s_world)?;
+	#[allow(unused)]
+	pub fn from_string(source: &str, dest: &mut world::World) -> io::Result<()> {
+		let src = Self::migrate(serde_json::from_str(source)?)?;
+		Self::restore_snapshot(&src, dest);
 		Ok(())
 	}
 
+	/// In the format given by the file extension
+	pub fn save(file_path: &path::Path, world: &world::World) -> io::Result<()> {
+		Self::write(file_path, &Self::save_snapshot(world))
+	}
+
+	/// In any supported format, regardless of the file extension
 	pub fn load(file_path: &path::Path, world: &mut world::World) -> io::Result<()> {
-		let in_file = fs::File::open(file_path)?;
-		let src = serde_json::from_reader(in_file)?;
+		let src = Self::read(file_path)?;
 		Self::restore_snapshot(&src, world);
 		Ok(())
 	}
+
+	pub fn write(file_path: &path::Path, snapshot: &World) -> io::Result<()> {
+		let out_file = io::BufWriter::new(fs::File::create(file_path)?);
+		match SnapshotFormat::from_path(file_path) {
+			SnapshotFormat::Json => serde_json::to_writer_pretty(out_file, snapshot)?,
+			SnapshotFormat::Cbor => serde_cbor::to_writer(out_file, snapshot).map_err(cbor_error)?,
+			SnapshotFormat::CompressedCbor => {
+				let mut encoder = GzEncoder::new(out_file, flate2::Compression::default());
+				serde_cbor::to_writer(&mut encoder, snapshot).map_err(cbor_error)?;
+				encoder.finish()?;
+			}
+		}
+		Ok(())
+	}
+
+	pub fn read(file_path: &path::Path) -> io::Result<World> {
+		let mut bytes = Vec::new();
+		fs::File::open(file_path)?.read_to_end(&mut bytes)?;
+		Self::decode(&bytes)
+	}
+
+	fn decode(bytes: &[u8]) -> io::Result<World> {
+		let value = match SnapshotFormat::detect(bytes) {
+			SnapshotFormat::Json => serde_json::from_slice(bytes)?,
+			SnapshotFormat::Cbor => serde_cbor::from_slice(bytes).map_err(cbor_error)?,
+			SnapshotFormat::CompressedCbor => {
+				let mut inflated = Vec::new();
+				GzDecoder::new(bytes).read_to_end(&mut inflated)?;
+				return Self::decode(&inflated);
+			}
+		};
+		Self::migrate(value)
+	}
+
+	/// Rewrites a snapshot in the format given by the extension of the output
+	/// file, bringing it up to the current version on the way
+	pub fn convert(input: &path::Path, output: &path::Path) -> io::Result<()> {
+		let snapshot = Self::read(input)?;
+		Self::write(output, &snapshot)
+	}
 }
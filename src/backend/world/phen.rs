@@ -27,10 +27,31 @@ pub fn phenotype_of(agent_type: agent::AgentType) -> Box<dyn Phenotype> {
 		agent::AgentType::Minion => Box::new(Minion {}),
 		agent::AgentType::Spore => Box::new(Spore {}),
 		agent::AgentType::Player => Box::new(Player {}),
+		agent::AgentType::Prop => Box::new(Prop {}),
 		_ => Box::new(Resource {}),
 	}
 }
 
+/// The DNA of a prop is its width and height, as little endian floats, so
+/// that obstacles are restored from snapshots like any other agent
+pub fn prop_dna(width: f32, height: f32) -> Dna {
+	let mut dna = Vec::with_capacity(8);
+	dna.extend_from_slice(&width.to_bits().to_le_bytes());
+	dna.extend_from_slice(&height.to_bits().to_le_bytes());
+	dna.into_boxed_slice()
+}
+
+fn prop_size(dna: &[u8]) -> (f32, f32) {
+	let read = |i: usize| {
+		dna.get(i * 4..i * 4 + 4)
+			.map(|b| f32::from_bits(u32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+			.filter(|v| v.is_finite())
+			.map(|v| v.max(PROP_MIN_SIZE))
+			.unwrap_or(PROP_MIN_SIZE)
+	};
+	(read(0), read(1))
+}
+
 struct Resource;
 
 struct Minion;
@@ -39,6 +60,8 @@ struct Player;
 
 struct Spore;
 
+struct Prop;
+
 impl Phenotype for Resource {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &dyn Timer) -> agent::Agent {
 		gen.next_integer::<u8>(0, 3);
@@ -85,6 +108,7 @@ impl Phenotype for Player {
 
 impl Phenotype for Minion {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &dyn Timer) -> agent::Agent {
+		// the order of the reads up to the body plan is mirrored in decode::gene_names
 		let gender = gen.next_integer::<u8>(0, 3);
 		let tint = gen.next_float(0., 1.);
 		let albedo = color::Hsl::new(tint, 0.5, 0.5);
@@ -190,6 +214,23 @@ impl Phenotype for Spore {
 	}
 }
 
+impl Phenotype for Prop {
+	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &dyn Timer) -> agent::Agent {
+		let (width, height) = prop_size(&gen.dna_cloned());
+		let albedo = color::YPbPr::new(0.3, 0., 0.);
+		let body = Shape::new_box(height / 2., width / height);
+		let mut builder = AgentBuilder::new(
+			id,
+			Material::default(),
+			Livery { albedo: albedo.to_rgba(), ..Default::default() },
+			gen.dna_cloned(),
+			segment::State::with_charge(1., 1., seconds(DEFAULT_CHARGE_DECAY_TIME)),
+		);
+		// props never grow
+		builder.maturity(1.).start(initial_state.transform, Motion::default(), &body).build(timer)
+	}
+}
+
 pub struct AgentBuilder {
 	id: Id,
 	material: Material,
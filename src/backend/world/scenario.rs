@@ -0,0 +1,186 @@
+//! Layouts of new worlds, for levels and repeatable test arenas. Scenarios
+//! are read from TOML or JSON files, where anything left out keeps the layout
+//! given by the configuration
+use backend::world;
+use backend::world::boundary::Boundary;
+use backend::world::gen;
+use core::clock::{seconds, SecondsValue};
+use core::geometry::{Position, Rect, Transform};
+use rand::Rng;
+use serde_json;
+use std::f32::consts;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path;
+use toml;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Extent {
+	pub left: f32,
+	pub bottom: f32,
+	pub right: f32,
+	pub top: f32,
+}
+
+fn one() -> f32 { 1. }
+
+fn yes() -> bool { true }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeederSpec {
+	pub x: f32,
+	pub y: f32,
+	/// Seconds between two resources. The configured period if left out
+	#[serde(default)]
+	pub rate: Option<SecondsValue>,
+	/// Relative to the default, for both the light of the feeder and the
+	/// charge of its resources
+	#[serde(default = "one")]
+	pub intensity: f32,
+	/// Whether the feeder drifts around the centre of the world, or stays put
+	#[serde(default = "yes")]
+	pub orbit: bool,
+	/// Delay of its seasons, as a fraction of a year, on top of the configured
+	/// one between consecutive feeders
+	#[serde(default)]
+	pub phase: f32,
+}
+
+/// A rectangle, centred on its position and rotated by its angle in radians
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObstacleSpec {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+	#[serde(default)]
+	pub angle: f32,
+}
+
+/// A disc where founder minions are placed after an extinction
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnRegion {
+	pub x: f32,
+	pub y: f32,
+	pub radius: f32,
+}
+
+impl SpawnRegion {
+	/// A point taken uniformly at random in the region
+	pub fn random_point<R>(&self, rng: &mut R) -> Position
+	where R: Rng {
+		let r = self.radius * rng.next_f32().sqrt();
+		let (s, c) = (consts::PI * 2. * rng.next_f32()).sin_cos();
+		Position::new(self.x + r * c, self.y + r * s)
+	}
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+	pub name: Option<String>,
+	pub extent: Option<Extent>,
+	pub boundary: Option<Boundary>,
+	/// No feeders at all if empty, a ring of them as configured if left out
+	pub feeders: Option<Vec<FeederSpec>>,
+	/// Gene pool files, relative to the scenario file
+	pub minion_gene_pool: Option<path::PathBuf>,
+	pub resource_gene_pool: Option<path::PathBuf>,
+	pub obstacles: Vec<ObstacleSpec>,
+	/// Founders spawn in a spiral around the centre if there are none
+	pub spawn_regions: Vec<SpawnRegion>,
+	/// Contents of the gene pool files, read along with the scenario
+	#[serde(skip)]
+	gene_pools: (Option<Vec<u8>>, Option<Vec<u8>>),
+}
+
+fn invalid_data<E>(e: E) -> io::Error
+where E: ToString {
+	io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+fn read_gene_pool(dir: &path::Path, file_name: &Option<path::PathBuf>) -> io::Result<Option<Vec<u8>>> {
+	match *file_name {
+		None => Ok(None),
+		Some(ref file_name) => {
+			let data = fs::read(dir.join(file_name))
+				.map_err(|e| io::Error::new(e.kind(), format!("Could not load {:?}: {}", file_name, e)))?;
+			let gene_pool = gen::GenePool::parse_from_resource(&data)
+				.map_err(|e| io::Error::new(e.kind(), format!("Invalid gene pool {:?}: {}", file_name, e)))?;
+			if gene_pool.len() == 0 {
+				return Err(invalid_data(format!("Empty gene pool {:?}", file_name)));
+			}
+			Ok(Some(data))
+		}
+	}
+}
+
+impl Scenario {
+	/// TOML for .toml files, JSON otherwise. The gene pool files are read
+	/// right away
+	pub fn load(file_path: &path::Path) -> io::Result<Self> {
+		let text = fs::read_to_string(file_path)?;
+		let mut scenario: Scenario = if file_path.extension().and_then(OsStr::to_str) == Some("toml") {
+			toml::from_str(&text).map_err(invalid_data)?
+		} else {
+			serde_json::from_str(&text).map_err(invalid_data)?
+		};
+		let dir = file_path.parent().unwrap_or_else(|| path::Path::new(""));
+		scenario.gene_pools =
+			(read_gene_pool(dir, &scenario.minion_gene_pool)?, read_gene_pool(dir, &scenario.resource_gene_pool)?);
+		Ok(scenario)
+	}
+
+	/// Lays out a new world, before any other agent is added to it
+	pub fn apply(&self, world: &mut world::World) {
+		if let Some(ref name) = self.name {
+			info!("Scenario: {}", name);
+		}
+		if let Some(extent) = self.extent {
+			world.extent = Rect::new(extent.left, extent.bottom, extent.right, extent.top);
+		}
+		if let Some(boundary) = self.boundary {
+			world.boundary = boundary;
+		}
+		if let Some(ref feeders) = self.feeders {
+			let default_rate = world.config().emitter_period;
+			let feeders = feeders
+				.iter()
+				.map(|spec| {
+					let mut feeder = world::Feeder::new(spec.x, spec.y, seconds(spec.rate.unwrap_or(default_rate)));
+					if let Some(rate) = spec.rate {
+						feeder.set_rate(seconds(rate));
+					}
+					feeder.set_power(spec.intensity);
+					feeder.set_orbiting(spec.orbit);
+					feeder.set_phase(spec.phase);
+					feeder
+				})
+				.collect();
+			world.set_feeders(feeders);
+		}
+		let mutation_rate = world.config().mutation_rate;
+		// the gene pools were checked when the scenario was loaded
+		if let Some(Ok(gene_pool)) = self.gene_pools.0.as_ref().map(|data| gen::GenePool::parse_from_resource(data)) {
+			world.minion_gene_pool = gene_pool;
+			world.minion_gene_pool.set_mutation_rate(mutation_rate);
+		}
+		if let Some(Ok(gene_pool)) = self.gene_pools.1.as_ref().map(|data| gen::GenePool::parse_from_resource(data)) {
+			world.resource_gene_pool = gene_pool;
+			world.resource_gene_pool.set_mutation_rate(mutation_rate);
+		}
+		for obstacle in &self.obstacles {
+			world.new_obstacle(
+				Transform::new(Position::new(obstacle.x, obstacle.y), obstacle.angle),
+				obstacle.width,
+				obstacle.height,
+			);
+		}
+		world.spawn_regions = self.spawn_regions.clone();
+	}
+}
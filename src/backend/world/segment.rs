@@ -8,7 +8,7 @@ use core::math;
 use core::math::ExponentialFilter;
 use num::Zero;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PilotRotation {
 	None,
 	Orientation(Position),
@@ -17,7 +17,7 @@ pub enum PilotRotation {
 	FromVelocity,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Intent {
 	Idle,
 	Move(Position),
@@ -141,16 +141,18 @@ impl Segment {
 	pub fn growing_scaled_vertex(&self, index: usize) -> Position {
 		self.state.maturity * self.mesh.scaled_vertex(index)
 	}
-}
 
-impl obj::Drawable for Segment {
-	fn color(&self) -> Rgba {
-		let rgba = self.livery.albedo;
+	/// The segment color, with a different albedo
+	pub fn tinted_color(&self, rgba: Rgba) -> Rgba {
 		let c = 5. * ((self.state.charge.get() * 0.99) + 0.01);
 		[rgba[0] * c, rgba[1] * c, rgba[2] * c, rgba[3] * self.material.density]
 	}
 }
 
+impl obj::Drawable for Segment {
+	fn color(&self) -> Rgba { self.tinted_color(self.livery.albedo) }
+}
+
 impl Transformable for Segment {
 	fn transform(&self) -> &Transform { &self.transform }
 
@@ -0,0 +1,149 @@
+//! Policies choosing which genome of a gene pool seeds the next agent
+use app::constants::*;
+use rand::Rng;
+use std::cmp;
+
+pub trait Selection: Send + Sync {
+	/// Index of the next genome, given the fitness of each genome in the pool
+	/// and the index that comes next in round-robin order
+	fn select(&mut self, fitness: &[f32], next: usize, rng: &mut dyn Rng) -> usize;
+}
+
+/// Every genome in turn, as the gene pools always did
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RoundRobin;
+
+impl Selection for RoundRobin {
+	fn select(&mut self, _: &[f32], next: usize, _: &mut dyn Rng) -> usize { next }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniformRandom;
+
+impl Selection for UniformRandom {
+	fn select(&mut self, fitness: &[f32], _: usize, rng: &mut dyn Rng) -> usize { random_index(fitness.len(), rng) }
+}
+
+/// Roulette wheel: the chance of a genome is proportional to its fitness.
+/// Uniform if no genome has a positive fitness
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FitnessWeighted;
+
+impl Selection for FitnessWeighted {
+	fn select(&mut self, fitness: &[f32], _: usize, rng: &mut dyn Rng) -> usize {
+		let total: f32 = fitness.iter().map(|f| f.max(0.)).sum();
+		if total <= 0. {
+			return random_index(fitness.len(), rng);
+		}
+		let mut target = rng.next_f32() * total;
+		for (i, f) in fitness.iter().enumerate() {
+			target -= f.max(0.);
+			if target < 0. {
+				return i;
+			}
+		}
+		fitness.iter().rposition(|f| *f > 0.).unwrap_or(0)
+	}
+}
+
+/// The fittest of `size` genomes drawn at random. Larger tournaments make for
+/// a stronger selection pressure
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+	pub size: usize,
+}
+
+impl Default for Tournament {
+	fn default() -> Self { Tournament { size: SELECTION_TOURNAMENT_SIZE } }
+}
+
+impl Selection for Tournament {
+	fn select(&mut self, fitness: &[f32], _: usize, rng: &mut dyn Rng) -> usize {
+		(0..self.size.max(1))
+			.map(|_| random_index(fitness.len(), rng))
+			.max_by(|a, b| fitness[*a].partial_cmp(&fitness[*b]).unwrap_or(cmp::Ordering::Equal))
+			.unwrap_or(0)
+	}
+}
+
+fn random_index(len: usize, rng: &mut dyn Rng) -> usize {
+	if len == 0 {
+		0
+	} else {
+		((rng.next_f32() * len as f32) as usize).min(len - 1)
+	}
+}
+
+/// `round-robin`, `random`, `fitness`, `tournament` or `tournament:N`
+pub fn parse(s: &str) -> Option<Box<dyn Selection>> {
+	let mut parts = s.splitn(2, ':');
+	match (parts.next(), parts.next()) {
+		(Some("round-robin"), None) => Some(Box::new(RoundRobin)),
+		(Some("random"), None) => Some(Box::new(UniformRandom)),
+		(Some("fitness"), None) => Some(Box::new(FitnessWeighted)),
+		(Some("tournament"), None) => Some(Box::new(Tournament::default())),
+		(Some("tournament"), Some(size)) => size
+			.parse::<usize>()
+			.ok()
+			.filter(|s| *s > 0)
+			.map(|size| Box::new(Tournament { size }) as Box<dyn Selection>),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::rng::XorShiftRng;
+
+	const SEED: u64 = 12345;
+
+	fn picks(selection: &mut dyn Selection, fitness: &[f32], count: usize) -> Vec<usize> {
+		let mut rng = XorShiftRng::from_seed(SEED);
+		(0..count).map(|i| selection.select(fitness, i % fitness.len(), &mut rng)).collect()
+	}
+
+	#[test]
+	fn parse_every_policy() {
+		let fitness = [0., 0., 0., 5.];
+		for name in &["round-robin", "random", "fitness", "tournament", "tournament:3"] {
+			assert!(parse(name).is_some(), "{}", name);
+		}
+		assert_eq!(picks(&mut *parse("round-robin").unwrap(), &fitness, 4), vec![0, 1, 2, 3]);
+		assert!(picks(&mut *parse("tournament:16").unwrap(), &fitness, 10).iter().all(|i| *i == 3));
+		for name in &["", "roulette", "random:2", "tournament:0", "tournament:x", "round-robin:1"] {
+			assert!(parse(name).is_none(), "{}", name);
+		}
+	}
+
+	#[test]
+	fn fitness_weighted_skips_the_unfit() {
+		let fitness = [0., 2., 0., -1., 1., 0.];
+		let picks = picks(&mut FitnessWeighted, &fitness, 1000);
+		assert!(picks.iter().all(|i| fitness[*i] > 0.));
+		// roughly twice as often as the other one
+		let ones = picks.iter().filter(|i| **i == 1).count();
+		assert!(ones > 600 && ones < 730, "{}", ones);
+	}
+
+	#[test]
+	fn fitness_weighted_uniform_without_fitness() {
+		let fitness = [0., 0., -1.];
+		let picks = picks(&mut FitnessWeighted, &fitness, 300);
+		assert!((0..fitness.len()).all(|i| picks.contains(&i)));
+	}
+
+	#[test]
+	fn large_tournament_picks_the_fittest() {
+		let fitness = [3., 1., 7., 2., 7.5, 0.];
+		let picks = picks(&mut Tournament { size: fitness.len() * 8 }, &fitness, 100);
+		assert!(picks.iter().all(|i| *i == 4));
+	}
+
+	#[test]
+	fn uniform_random_in_range() {
+		let picks = picks(&mut UniformRandom, &[1.; 5], 500);
+		assert!(picks.iter().all(|i| *i < 5));
+		assert!((0..5).all(|i| picks.contains(&i)));
+	}
+}
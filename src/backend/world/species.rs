@@ -0,0 +1,141 @@
+//! Clustering of the minion genomes into species
+use app::constants::*;
+use backend::obj::Id;
+use backend::world::gen;
+use backend::world::gen::Dna;
+use core::color;
+use core::color::{Rgba, ToRgb};
+use std::collections::BTreeMap;
+
+pub type SpeciesId = usize;
+
+#[derive(Clone, Debug)]
+pub struct Species {
+	id: SpeciesId,
+	representative: Dna,
+	size: usize,
+}
+
+impl Species {
+	pub fn id(&self) -> SpeciesId { self.id }
+
+	/// The member with the smallest total distance from all the others
+	pub fn representative(&self) -> &Dna { &self.representative }
+
+	pub fn size(&self) -> usize { self.size }
+}
+
+/// A distinct, stable color for each species
+pub fn tint(id: SpeciesId) -> Rgba {
+	const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+	color::Hsl::new((id as f32 * GOLDEN_RATIO_CONJUGATE).fract(), 0.5, 0.5).to_rgba()
+}
+
+/// Species are clusters of minions whose genomes are within a threshold Hamming
+/// distance from the representative of their species. Each clustering starts
+/// from the representatives found by the previous one, so that species keep
+/// their id for as long as they have members
+#[derive(Clone, Debug)]
+pub struct SpeciesMap {
+	threshold: usize,
+	next_id: SpeciesId,
+	species: BTreeMap<SpeciesId, Species>,
+	members: BTreeMap<Id, SpeciesId>,
+}
+
+impl Default for SpeciesMap {
+	fn default() -> Self {
+		SpeciesMap {
+			threshold: SPECIES_DISTANCE_THRESHOLD,
+			next_id: 1,
+			species: BTreeMap::new(),
+			members: BTreeMap::new(),
+		}
+	}
+}
+
+impl SpeciesMap {
+	pub fn threshold(&self) -> usize { self.threshold }
+
+	pub fn set_threshold(&mut self, threshold: usize) { self.threshold = threshold }
+
+	pub fn next_id(&self) -> SpeciesId { self.next_id }
+
+	pub fn len(&self) -> usize { self.species.len() }
+
+	pub fn is_empty(&self) -> bool { self.species.is_empty() }
+
+	pub fn get(&self, id: SpeciesId) -> Option<&Species> { self.species.get(&id) }
+
+	pub fn iter(&self) -> impl Iterator<Item = &Species> { self.species.values() }
+
+	/// Minions born after the last clustering belong to no species yet
+	pub fn species_of(&self, id: Id) -> Option<SpeciesId> { self.members.get(&id).cloned() }
+
+	/// Species sizes, largest first
+	pub fn sizes(&self) -> Vec<usize> {
+		let mut sizes = self.species.values().map(Species::size).collect::<Vec<_>>();
+		sizes.sort_by(|a, b| b.cmp(a));
+		sizes
+	}
+
+	pub fn clear(&mut self) {
+		self.species.clear();
+		self.members.clear();
+	}
+
+	/// Species without members, from a snapshot
+	pub fn restore(&mut self, next_id: SpeciesId, representatives: Vec<(SpeciesId, Dna)>) {
+		self.clear();
+		self.next_id = next_id;
+		for (id, representative) in representatives {
+			self.species.insert(id, Species { id, representative, size: 0 });
+		}
+	}
+
+	pub fn cluster(&mut self, minions: &[(Id, Dna)]) {
+		struct Cluster {
+			id: Option<SpeciesId>,
+			representative: Dna,
+			members: Vec<usize>,
+		}
+		let mut clusters = self
+			.species
+			.values()
+			.map(|s| Cluster { id: Some(s.id), representative: s.representative.clone(), members: Vec::new() })
+			.collect::<Vec<_>>();
+
+		for (i, (_, dna)) in minions.iter().enumerate() {
+			let nearest = clusters
+				.iter()
+				.enumerate()
+				.map(|(c, cluster)| (c, gen::hamming_distance(&cluster.representative, dna)))
+				.filter(|&(_, distance)| distance <= self.threshold)
+				.min_by_key(|&(_, distance)| distance);
+			match nearest {
+				Some((c, _)) => clusters[c].members.push(i),
+				None => clusters.push(Cluster { id: None, representative: dna.clone(), members: vec![i] }),
+			}
+		}
+
+		self.clear();
+		for cluster in clusters.into_iter().filter(|c| !c.members.is_empty()) {
+			let id = cluster.id.unwrap_or_else(|| {
+				self.next_id += 1;
+				self.next_id - 1
+			});
+			let medoid = cluster
+				.members
+				.iter()
+				.min_by_key(|&&i| {
+					cluster.members.iter().map(|&j| gen::hamming_distance(&minions[i].1, &minions[j].1)).sum::<usize>()
+				})
+				.map(|&i| minions[i].1.clone())
+				.unwrap_or(cluster.representative);
+			for &i in &cluster.members {
+				self.members.insert(minions[i].0, id);
+			}
+			self.species.insert(id, Species { id, representative: medoid, size: cluster.members.len() });
+		}
+	}
+}
@@ -6,8 +6,7 @@ use backend::world::agent::TypedAgent;
 use backend::world::gen::*;
 use backend::world::phen;
 use core::clock::Timer;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 
 pub struct Swarm {
 	seq: Id,
@@ -18,7 +17,7 @@ pub struct Swarm {
 
 impl Swarm {
 	pub fn new(agent_type: AgentType, phenotype: Box<dyn phen::Phenotype>) -> Swarm {
-		Swarm { seq: 0, agent_type, phenotype, agents: HashMap::new() }
+		Swarm { seq: 0, agent_type, phenotype, agents: BTreeMap::new() }
 	}
 
 	#[allow(dead_code)]
@@ -45,10 +44,8 @@ impl Swarm {
 	}
 
 	pub fn free_resources(&mut self, freed: &mut Vec<Agent>) {
-		let mut dead = HashSet::new();
-		for id in self.agents.iter().filter(|&(_, agent)| !agent.state.is_alive()).map(|(&id, _)| id) {
-			dead.insert(id);
-		}
+		let dead =
+			self.agents.iter().filter(|&(_, agent)| !agent.state.is_alive()).map(|(&id, _)| id).collect::<Vec<_>>();
 		for id in &dead {
 			if let Some(agent) = self.agents.remove(&id) {
 				freed.push(agent);
@@ -87,9 +84,10 @@ impl Swarm {
 		self.insert(entity)
 	}
 
-	pub fn agents(&self) -> &HashMap<Id, Agent> { &self.agents }
+	pub fn agents(&self) -> &agent::AgentMap { &self.agents }
 
-	pub fn agents_mut(&mut self) -> &mut HashMap<Id, Agent> { &mut self.agents }
+	pub fn agents_mut(&mut self) -> &mut agent::AgentMap { &mut self.agents }
 }
 
-pub type SwarmMap = HashMap<AgentType, Swarm>;
+// ordered maps keep iteration, and therefore simulation, deterministic
+pub type SwarmMap = BTreeMap<AgentType, Swarm>;
@@ -10,7 +10,7 @@ use std::time;
 pub type SecondsValue = f64;
 pub type SpeedFactor = SecondsValue;
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Seconds(SecondsValue);
 
 const ZERO_SECONDS: Seconds = Seconds(0.0);
@@ -192,6 +192,13 @@ impl Hourglass {
 		Hourglass { stopwatch: TimerStopwatch::new(timer), capacity: seconds, timeout: seconds }
 	}
 
+	/// An hourglass with some time already gone, as restored from a snapshot
+	pub fn resume(capacity: Seconds, left: Seconds, timer: &dyn Timer) -> Self {
+		Hourglass { stopwatch: TimerStopwatch::new(timer), capacity, timeout: left }
+	}
+
+	pub fn capacity(&self) -> Seconds { self.capacity }
+
 	pub fn renew<T>(&mut self, timer: &T)
 	where T: Timer {
 		self.timeout = self.capacity;
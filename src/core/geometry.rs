@@ -19,13 +19,13 @@ pub struct Size {
 	pub height: f32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transform {
 	pub position: Position,
 	pub angle: Angle,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Motion {
 	pub velocity: Velocity,
 	pub spin: Spin,
@@ -3,5 +3,6 @@ pub mod color;
 pub mod geometry;
 pub mod math;
 pub mod resource;
+pub mod rng;
 pub mod util;
 pub mod view;
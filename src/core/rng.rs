@@ -0,0 +1,66 @@
+use rand;
+use rand::Rng;
+
+pub type Seed = u64;
+
+pub type RngState = [u32; 4];
+
+/// Xorshift128 generator. Unlike the ones provided by rand, its internal state
+/// can be read back, so that it can be stored and restored with a snapshot
+#[derive(Clone, Debug)]
+pub struct XorShiftRng {
+	state: RngState,
+}
+
+// splitmix64, used to spread a seed over the whole state
+fn mix(seed: &mut u64) -> u64 {
+	*seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+	let mut z = *seed;
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	z ^ (z >> 31)
+}
+
+pub fn random_seed() -> Seed { rand::thread_rng().gen() }
+
+impl XorShiftRng {
+	pub fn from_seed(seed: Seed) -> Self {
+		let mut s = seed;
+		let (a, b) = (mix(&mut s), mix(&mut s));
+		let state = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+		if state == [0u32; 4] {
+			// an all-zero state would only ever generate zeros
+			XorShiftRng { state: [0x193a_6754, 0xa8a7_d469, 0x9783_0e05, 0x113b_a7bb] }
+		} else {
+			XorShiftRng { state }
+		}
+	}
+
+	pub fn from_state(state: RngState) -> Self {
+		if state == [0u32; 4] {
+			Self::from_seed(0)
+		} else {
+			XorShiftRng { state }
+		}
+	}
+
+	pub fn state(&self) -> RngState { self.state }
+
+	/// A new, independent generator derived from the current state, which is
+	/// left untouched
+	pub fn fork(&self) -> Self {
+		let [x, y, z, w] = self.state;
+		Self::from_seed((u64::from(x) << 32 | u64::from(y)) ^ (u64::from(z) << 32 | u64::from(w)).rotate_left(17))
+	}
+}
+
+impl rand::Rng for XorShiftRng {
+	#[inline]
+	fn next_u32(&mut self) -> u32 {
+		let [x, y, z, w] = self.state;
+		let t = x ^ (x << 11);
+		let next = w ^ (w >> 19) ^ (t ^ (t >> 8));
+		self.state = [y, z, w, next];
+		next
+	}
+}
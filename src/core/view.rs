@@ -21,6 +21,10 @@ impl Viewport {
 	}
 
 	pub fn scale(&mut self, scale: f32) { self.scale = scale; }
+
+	pub fn width(&self) -> u32 { self.width }
+
+	pub fn height(&self) -> u32 { self.height }
 }
 
 impl WorldTransform for Viewport {
@@ -134,7 +134,8 @@ impl AlertPlayer<app::Event, self::Error> for SoundSystemAlertPlayer<ThreadedSou
 			| Event::SaveWorldToFile
 			| Event::DeselectAll
 			| Event::ZoomReset
-			| Event::ToggleDebug => SoundEffect::UserOption,
+			| Event::ToggleDebug
+			| Event::ToggleSpeciesTint => SoundEffect::UserOption,
 
 			Event::PickMinion(_) => SoundEffect::SelectMinion,
 
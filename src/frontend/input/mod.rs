@@ -1,6 +1,8 @@
 //! Input state, including current mouse position and button click
+#[cfg(feature = "gamepad")]
 pub mod gamepad;
 
+#[cfg(feature = "gamepad")]
 pub use self::gamepad::GamepadEventLoop;
 
 use bit_set::BitSet;
@@ -29,6 +31,7 @@ const MAX_AXIS: usize = 6;
 
 #[derive(Clone)]
 pub struct GamepadState {
+	#[cfg(feature = "gamepad")]
 	pub connected: bool,
 	pub button_pressed: BitSet,
 	pub button_pressed_last: BitSet,
@@ -48,6 +51,7 @@ pub struct InputState {
 impl Default for GamepadState {
 	fn default() -> Self {
 		GamepadState {
+			#[cfg(feature = "gamepad")]
 			connected: false,
 			button_pressed: BitSet::new(),
 			button_pressed_last: BitSet::new(),
@@ -267,6 +271,7 @@ pub trait InputRead {
 	fn key_once(&self, b: Key) -> bool;
 	fn any_key_pressed(&self, b: &[Key]) -> bool;
 	fn any_ctrl_pressed(&self) -> bool;
+	fn any_shift_pressed(&self) -> bool;
 	fn any_alt_pressed(&self) -> bool;
 	fn any_super_pressed(&self) -> bool;
 	fn chord_pressed(&self, b: &[Key]) -> bool;
@@ -292,6 +297,8 @@ impl InputRead for InputState {
 
 	fn any_ctrl_pressed(&self) -> bool { self.any_key_pressed(&[Key::LCtrl, Key::RCtrl]) }
 
+	fn any_shift_pressed(&self) -> bool { self.any_key_pressed(&[Key::LShift, Key::RShift]) }
+
 	fn any_alt_pressed(&self) -> bool { self.any_key_pressed(&[Key::LAlt, Key::RAlt]) }
 
 	fn any_super_pressed(&self) -> bool { self.any_key_pressed(&[Key::LSuper, Key::RSuper]) }
@@ -407,6 +414,7 @@ impl InputState {
 	}
 }
 
+#[cfg(any(feature = "graphics", feature = "gamepad"))]
 pub trait EventMapper<T> {
 	fn translate(&self, e: &T) -> Option<Event>;
 }
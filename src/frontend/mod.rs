@@ -1,5 +1,8 @@
+#[cfg(feature = "audio")]
 pub mod audio;
+#[cfg(feature = "graphics")]
 pub mod gfx_window_glutin;
 pub mod input;
+#[cfg(feature = "graphics")]
 pub mod render;
 pub mod ui;
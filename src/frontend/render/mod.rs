@@ -2,6 +2,7 @@ mod effects;
 pub mod formats;
 #[macro_use]
 mod forward;
+mod software;
 
 use core::geometry::Position;
 use core::geometry::M44;
@@ -13,6 +14,8 @@ use frontend::render::forward::PrimitiveIndex;
 use frontend::render::forward::Vertex;
 use frontend::render::forward::VertexIndex;
 
+pub use self::software::SoftwareRenderer;
+
 use std::convert;
 use std::fmt;
 use std::result;
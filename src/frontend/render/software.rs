@@ -0,0 +1,297 @@
+//! Rasterization of the draw primitives on the CPU, into an RGBA image, for
+//! machines with no GPU. Shading is much simpler than the forward renderer's:
+//! each primitive is lit once, at its centre, and there are no post effects
+//! other than tone mapping
+use super::*;
+use cgmath::Transform;
+use image;
+use std::io;
+use std::path;
+
+const DIFFUSE_GAIN: f32 = 0.25;
+const LAMBERT_GAIN: f32 = 0.2;
+/// Height of the lights above the plane of the world, as in the forward
+/// renderer
+const LIGHT_HEIGHT: f32 = 2.0;
+const DEFAULT_EXPOSURE: f32 = 1.0;
+const GAMMA: f32 = 2.2;
+
+#[derive(Clone, Copy)]
+enum Blend {
+	Alpha,
+	Additive,
+}
+
+pub struct SoftwareRenderer {
+	width: u32,
+	height: u32,
+	/// Linear HDR colours, top row first
+	pixels: Vec<formats::Rgba>,
+	background_color: formats::Rgba,
+	view_projection: M44,
+	lights: Vec<(Position, formats::Rgba, formats::Rgba)>,
+	exposure: f32,
+}
+
+impl SoftwareRenderer {
+	pub fn new(width: u32, height: u32) -> Self {
+		use cgmath::SquareMatrix;
+		SoftwareRenderer {
+			width,
+			height,
+			pixels: vec![BACKGROUND; (width * height) as usize],
+			background_color: BACKGROUND,
+			view_projection: M44::identity(),
+			lights: Vec::new(),
+			exposure: DEFAULT_EXPOSURE,
+		}
+	}
+
+	pub fn width(&self) -> u32 { self.width }
+
+	pub fn height(&self) -> u32 { self.height }
+
+	pub fn set_exposure(&mut self, exposure: f32) { self.exposure = exposure; }
+
+	pub fn setup_frame(&mut self, camera: &Camera, background_color: formats::Rgba, lights: &[Light]) {
+		self.background_color = background_color;
+		self.view_projection = camera.projection * camera.view;
+		self.lights = lights
+			.iter()
+			.map(|light| match *light {
+				Light::PointLight { position, color, attenuation } => (position, color, attenuation),
+			})
+			.collect();
+	}
+
+	pub fn begin_frame(&mut self) {
+		for pixel in &mut self.pixels {
+			*pixel = self.background_color;
+		}
+	}
+
+	/// Reinhard tone mapping followed by gamma correction
+	pub fn to_image(&self) -> image::RgbaImage {
+		let exposure = self.exposure;
+		let map = |c: f32| {
+			let exposed = (c * exposure).max(0.);
+			((exposed / (1. + exposed)).powf(1. / GAMMA) * 255.).round() as u8
+		};
+		image::RgbaImage::from_fn(self.width, self.height, |x, y| {
+			let c = self.pixels[(y * self.width + x) as usize];
+			image::Rgba([map(c[0]), map(c[1]), map(c[2]), 255])
+		})
+	}
+
+	pub fn save_png(&self, file_path: &path::Path) -> io::Result<()> {
+		self.to_image()
+			.save_with_format(file_path, image::ImageFormat::Png)
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+	}
+
+	fn to_screen(&self, transform: &M44, p: [f32; 3]) -> Position {
+		let clip =
+			self.view_projection * transform.transform_point(cgmath::Point3::new(p[0], p[1], p[2])).to_homogeneous();
+		let ndc = Position::new(clip.x / clip.w, clip.y / clip.w);
+		Position::new((ndc.x + 1.) * 0.5 * self.width as f32, (1. - ndc.y) * 0.5 * self.height as f32)
+	}
+
+	/// Emissive colour plus the contribution of the lights at a point of the
+	/// world
+	fn lit(&self, color: formats::Rgba, position: Position) -> formats::Rgba {
+		let mut light = DIFFUSE_GAIN;
+		for &(center, light_color, attenuation) in &self.lights {
+			let d = center - position;
+			let distance = (d.x * d.x + d.y * d.y + LIGHT_HEIGHT * LIGHT_HEIGHT).sqrt();
+			let inv_distance = 1. / distance;
+			let intensity =
+				attenuation[0] + attenuation[1] * inv_distance + attenuation[2] * inv_distance * inv_distance;
+			let lambert = LIGHT_HEIGHT * inv_distance;
+			let luminance = (light_color[0] + light_color[1] + light_color[2]) / 3.;
+			light += LAMBERT_GAIN * luminance * intensity.max(0.) * lambert;
+		}
+		[color[0] * light, color[1] * light, color[2] * light, color[3]]
+	}
+
+	fn blend(&mut self, x: i32, y: i32, color: formats::Rgba, coverage: f32, blend: Blend) {
+		if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+			return;
+		}
+		let pixel = &mut self.pixels[(y as u32 * self.width + x as u32) as usize];
+		let alpha = (color[3] * coverage).max(0.).min(1.);
+		for (p, c) in pixel.iter_mut().zip(color.iter()).take(3) {
+			*p = match blend {
+				Blend::Alpha => *p * (1. - alpha) + c * alpha,
+				Blend::Additive => *p + c * alpha,
+			};
+		}
+	}
+
+	/// Fills a triangle, shading each pixel with the barycentric interpolation
+	/// of the texture coordinates
+	fn fill_triangle<F>(&mut self, p: [Position; 3], uv: [[f32; 2]; 3], blend: Blend, shade: F)
+	where F: Fn([f32; 2]) -> (formats::Rgba, f32) {
+		let area = (p[1].x - p[0].x) * (p[2].y - p[0].y) - (p[2].x - p[0].x) * (p[1].y - p[0].y);
+		if area.abs() < ::std::f32::EPSILON {
+			return;
+		}
+		let min_x = p.iter().map(|v| v.x).fold(f32::MAX, f32::min).floor().max(0.) as i32;
+		let max_x = p.iter().map(|v| v.x).fold(f32::MIN, f32::max).ceil().min(self.width as f32) as i32;
+		let min_y = p.iter().map(|v| v.y).fold(f32::MAX, f32::min).floor().max(0.) as i32;
+		let max_y = p.iter().map(|v| v.y).fold(f32::MIN, f32::max).ceil().min(self.height as f32) as i32;
+		let edge = |a: Position, b: Position, x: f32, y: f32| (b.x - a.x) * (y - a.y) - (x - a.x) * (b.y - a.y);
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+				let w0 = edge(p[1], p[2], px, py) / area;
+				let w1 = edge(p[2], p[0], px, py) / area;
+				let w2 = edge(p[0], p[1], px, py) / area;
+				if w0 >= 0. && w1 >= 0. && w2 >= 0. {
+					let t =
+						[w0 * uv[0][0] + w1 * uv[1][0] + w2 * uv[2][0], w0 * uv[0][1] + w1 * uv[1][1] + w2 * uv[2][1]];
+					let (color, coverage) = shade(t);
+					self.blend(x, y, color, coverage, blend);
+				}
+			}
+		}
+	}
+
+	fn draw_line(&mut self, a: Position, b: Position, color: formats::Rgba) {
+		let d = b - a;
+		let steps = d.x.abs().max(d.y.abs()).ceil().max(1.) as usize;
+		for i in 0..=steps {
+			let p = a + d * (i as f32 / steps as f32);
+			self.blend(p.x.floor() as i32, p.y.floor() as i32, color, 1., Blend::Alpha);
+		}
+	}
+
+	fn draw_ball_at(&mut self, center: Position, radius: f32, color: formats::Rgba) {
+		let min_x = (center.x - radius).floor() as i32;
+		let max_x = (center.x + radius).ceil() as i32;
+		let min_y = (center.y - radius).floor() as i32;
+		let max_y = (center.y + radius).ceil() as i32;
+		for y in min_y..=max_y {
+			for x in min_x..=max_x {
+				let dx = (x as f32 + 0.5 - center.x) / radius;
+				let dy = (y as f32 + 0.5 - center.y) / radius;
+				let r2 = dx * dx + dy * dy;
+				if r2 <= 1. {
+					self.blend(x, y, color, 1. - r2, Blend::Additive);
+				}
+			}
+		}
+	}
+
+	fn draw_primitive(&mut self, style: Style, vertices: &[&Vertex], transform: &M44, appearance: &Appearance) {
+		let color = appearance.color;
+		match style {
+			Style::Lines | Style::DebugLines => {
+				let a = self.to_screen(transform, vertices[0].pos);
+				let b = self.to_screen(transform, vertices[1].pos);
+				self.draw_line(a, b, color);
+			}
+			Style::Ball => {
+				let center = self.to_screen(transform, vertices[0].pos);
+				let edge = self.to_screen(transform, vertices[1].pos);
+				let d = edge - center;
+				self.draw_ball_at(center, (d.x * d.x + d.y * d.y).sqrt(), color);
+			}
+			Style::Wireframe => {
+				let p = [
+					self.to_screen(transform, vertices[0].pos),
+					self.to_screen(transform, vertices[1].pos),
+					self.to_screen(transform, vertices[2].pos),
+				];
+				for i in 0..3 {
+					self.draw_line(p[i], p[(i + 1) % 3], color);
+				}
+			}
+			Style::Particle => {
+				let p = [
+					self.to_screen(transform, vertices[0].pos),
+					self.to_screen(transform, vertices[1].pos),
+					self.to_screen(transform, vertices[2].pos),
+				];
+				let uv = [vertices[0].tex_coord, vertices[1].tex_coord, vertices[2].tex_coord];
+				self.fill_triangle(p, uv, Blend::Additive, |t| {
+					let (dx, dy) = (2. * t[0] - 1., 2. * t[1] - 1.);
+					(color, (1. - dx * dx - dy * dy).max(0.))
+				});
+			}
+			Style::Lit => {
+				let origin = transform.transform_point(cgmath::Point3::new(0., 0., 0.));
+				let lit = self.lit(color, Position::new(origin.x, origin.y));
+				let p = [
+					self.to_screen(transform, vertices[0].pos),
+					self.to_screen(transform, vertices[1].pos),
+					self.to_screen(transform, vertices[2].pos),
+				];
+				let uv = [vertices[0].tex_coord, vertices[1].tex_coord, vertices[2].tex_coord];
+				self.fill_triangle(p, uv, Blend::Alpha, |_| (lit, 1.));
+			}
+			Style::Flat | Style::Stage | Style::Count => {
+				let p = [
+					self.to_screen(transform, vertices[0].pos),
+					self.to_screen(transform, vertices[1].pos),
+					self.to_screen(transform, vertices[2].pos),
+				];
+				let uv = [vertices[0].tex_coord, vertices[1].tex_coord, vertices[2].tex_coord];
+				self.fill_triangle(p, uv, Blend::Alpha, |_| (color, 1.));
+			}
+		}
+	}
+
+	fn draw_indexed(
+		&mut self,
+		style: Style,
+		vertices: &[Vertex],
+		indices: &[VertexIndex],
+		transforms: &[M44],
+		appearances: &[Appearance],
+	) {
+		let arity = match style {
+			Style::Lines | Style::DebugLines => 2,
+			_ => 3,
+		};
+		for primitive in indices.chunks(arity).filter(|p| p.len() == arity) {
+			let v = primitive.iter().map(|i| &vertices[*i as usize]).collect::<Vec<_>>();
+			let index = v[0].primitive_index as usize;
+			if let (Some(transform), Some(appearance)) = (transforms.get(index), appearances.get(index)) {
+				self.draw_primitive(style, &v, transform, appearance);
+			}
+		}
+	}
+}
+
+impl PrimitiveSequence for SoftwareRenderer {
+	fn push_batch(&mut self, batch: PrimitiveBatch) -> Result<()> {
+		self.draw_indexed(batch.style, &batch.vertices, &batch.indices, &batch.transforms, &batch.appearances);
+		Ok(())
+	}
+
+	fn push_primitive(
+		&mut self,
+		shader: Style,
+		vertices: Vec<Vertex>,
+		indices: Vec<VertexIndex>,
+		transform: M44,
+		appearance: Appearance,
+	) -> Result<()> {
+		self.draw_indexed(shader, &vertices, &indices, &[transform], &[appearance]);
+		Ok(())
+	}
+}
+
+impl DrawBatch for SoftwareRenderer {
+	fn draw_batch(&mut self, batch: PrimitiveBatch) { self.push_batch(batch).expect("Could not draw batch"); }
+}
+
+impl DrawBuffer for SoftwareRenderer {
+	fn draw_buffer(&mut self, mut buffer: PrimitiveBuffer) {
+		for batch_list in buffer.batches.drain(..) {
+			for batch in batch_list {
+				self.push_batch(batch).expect("Could not draw batch");
+			}
+		}
+	}
+}
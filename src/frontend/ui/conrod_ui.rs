@@ -190,6 +190,17 @@ impl Screen {
 					"Extinctions",
 					&format!("{}", frame_update.simulation.extinctions),
 				);
+				let species = &frame_update.simulation.species;
+				txt_with_label(&mut ids_iter, &mut widgets, "Species", &match species.first() {
+					Some(largest) => format!("{} ({})", species.len(), largest),
+					None => String::from("-"),
+				});
+				txt_with_label(
+					&mut ids_iter,
+					&mut widgets,
+					"Season",
+					&format!("{}", frame_update.simulation.conditions),
+				);
 			}
 		};
 		widgets
@@ -228,7 +239,7 @@ where
 			..Default::default()
 		};
 		let style_value = text::Style { color: Some(conrod::color::GREEN), font_size: Some(14), ..Default::default() };
-		const MAX_HUD_LABELS: usize = 10;
+		const MAX_HUD_LABELS: usize = 12;
 		let ids = Ids {
 			help_canvas: ui.widget_id_generator().next(),
 			help_text: ui.widget_id_generator().next(),
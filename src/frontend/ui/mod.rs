@@ -1,7 +1,11 @@
+#[cfg(feature = "graphics")]
 pub mod conrod_gfx;
+#[cfg(feature = "graphics")]
 pub mod conrod_ui;
+#[cfg(feature = "graphics")]
 pub mod theme;
 
+#[cfg(feature = "graphics")]
 use app::FrameUpdate;
 
 pub trait AlertPlayer<T, E> {
@@ -18,12 +22,14 @@ impl<T> AlertPlayer<T, ()> for NullAlertPlayer {
 	fn play(&mut self, _: &T) -> Result<(), ()> { Ok(()) }
 }
 
+#[cfg(feature = "graphics")]
 #[derive(Debug, Copy, Clone)]
 pub enum Error {
 	FontLoader,
 	ResourceLoader,
 }
 
+#[cfg(feature = "graphics")]
 #[derive(Debug, Clone)]
 pub enum Screen {
 	#[allow(unused)]
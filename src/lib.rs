@@ -0,0 +1,90 @@
+//! Rust-oids simulation library.
+//!
+//! `backend` holds the world, its agents and the systems acting on them; `app`
+//! drives them, either interactively or in batch mode. The `rust-oids` binary
+//! is a thin client of this crate.
+
+#![allow(unknown_lints)]
+#![warn(clippy::all)]
+
+pub mod app;
+pub mod backend;
+pub mod core;
+mod frontend;
+
+pub use backend::simulation::Simulation;
+pub use backend::systems::{System, SystemMode, Systems};
+pub use backend::world::gen::{GenePool, Genome};
+pub use backend::world::phen::Phenotype;
+pub use backend::world::World;
+
+#[macro_use]
+extern crate log;
+extern crate chrono;
+extern crate csv;
+
+#[macro_use]
+extern crate bitflags;
+extern crate bit_set;
+extern crate cgmath;
+
+#[macro_use]
+extern crate serde_derive;
+
+extern crate flate2;
+extern crate serde;
+extern crate serde_cbor;
+extern crate serde_json;
+extern crate toml;
+
+extern crate wrapped2d;
+
+#[cfg(feature = "graphics")]
+#[macro_use]
+extern crate gfx;
+#[cfg(feature = "graphics")]
+extern crate gfx_device_gl;
+#[cfg(feature = "graphics")]
+extern crate gl;
+#[cfg(feature = "graphics")]
+extern crate glutin;
+#[cfg(feature = "graphics")]
+extern crate image;
+#[cfg(feature = "graphics")]
+extern crate winit;
+
+#[cfg(feature = "audio")]
+extern crate dasp;
+#[cfg(feature = "audio")]
+extern crate dasp_sample;
+#[cfg(feature = "audio")]
+extern crate dasp_signal;
+#[cfg(feature = "audio")]
+extern crate dasp_slice;
+#[cfg(feature = "audio")]
+extern crate pitch_calc;
+#[cfg(feature = "audio")]
+extern crate portaudio;
+
+extern crate itertools;
+extern crate num;
+extern crate num_traits;
+extern crate rand;
+
+#[macro_use]
+extern crate enum_primitive;
+#[cfg(feature = "graphics")]
+extern crate conrod;
+
+extern crate ctrlc;
+extern crate getopts;
+
+#[cfg(feature = "gamepad")]
+extern crate gilrs;
+
+extern crate dirs;
+extern crate rayon;
+#[cfg(all(feature = "audio", target_os = "linux"))]
+extern crate thread_priority;
+
+extern crate rustc_serialize as serialize;
@@ -0,0 +1,23 @@
+//! Reloading the configuration must not undo the changes made to the world
+extern crate rust_oids;
+
+use rust_oids::core::clock::seconds;
+use rust_oids::core::resource::filesystem::ResourceLoaderBuilder;
+use rust_oids::World;
+use std::path::Path;
+
+#[test]
+fn feeders_keep_their_own_period() {
+	let res = ResourceLoaderBuilder::new().add(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")).build();
+	let mut world = World::new(&res, "minion_gene_pool.csv", 12345);
+	assert!(world.feeders().len() > 1);
+	// a period of its own, even if it happens to be the configured one
+	let period = seconds(world.config().emitter_period);
+	world.feeders_mut()[0].set_rate(period);
+
+	let mut config = world.config().clone();
+	config.emitter_period *= 2.;
+	world.set_config(config.clone());
+	assert_eq!(world.feeder_period(0), period);
+	assert!((1..world.feeders().len()).all(|i| world.feeder_period(i) == seconds(config.emitter_period)));
+}
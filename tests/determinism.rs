@@ -0,0 +1,38 @@
+//! Runs started from the same seed must not diverge
+extern crate rust_oids;
+
+use rust_oids::backend::world::agent::AgentType;
+use rust_oids::core::clock::seconds;
+use rust_oids::core::resource::filesystem::ResourceLoaderBuilder;
+use rust_oids::core::rng::Seed;
+use rust_oids::Simulation;
+use std::path::Path;
+
+const STEPS: usize = 300;
+
+/// Id, energy and position of every minion after some steps
+fn run(seed: Seed) -> Vec<(usize, f32, f32, f32)> {
+	let res = ResourceLoaderBuilder::new().add(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")).build();
+	let mut simulation = Simulation::from_gene_pool(&res, "minion_gene_pool.csv", seed);
+	simulation.run(seconds(1. / 60.), STEPS);
+	simulation
+		.agents(AgentType::Minion)
+		.iter()
+		.map(|(id, agent)| {
+			let position = agent.segments[0].transform.position;
+			(*id, agent.state.energy(), position.x, position.y)
+		})
+		.collect()
+}
+
+#[test]
+fn same_seed_same_world() {
+	let first = run(12345);
+	assert!(!first.is_empty());
+	assert_eq!(first, run(12345));
+}
+
+#[test]
+fn different_seed_different_world() {
+	assert_ne!(run(12345), run(54321));
+}
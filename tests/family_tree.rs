@@ -0,0 +1,42 @@
+//! Family trees must survive long runs
+extern crate rust_oids;
+
+use rust_oids::backend::world::lineage::{FamilyTree, Lineage};
+use std::collections::BTreeSet;
+
+const GENERATIONS: usize = 100_000;
+
+/// A single line of descent, from founder 0 to the minion of the last
+/// generation
+fn line() -> FamilyTree {
+	let mut tree = FamilyTree::default();
+	tree.insert(0, Lineage::founder(0.));
+	for id in 1..GENERATIONS {
+		let parent = tree.get(id - 1).cloned().unwrap();
+		tree.insert(id, Lineage::offspring_of(id - 1, &parent, id as f64));
+	}
+	tree
+}
+
+#[test]
+fn write_deep_newick() {
+	let mut out = Vec::new();
+	line().write_newick(&mut out).unwrap();
+	let newick = String::from_utf8(out).unwrap();
+	assert!(newick.starts_with("(((("));
+	assert!(newick.contains(&format!("({}:1){}:1)", GENERATIONS - 1, GENERATIONS - 2)));
+	assert!(newick.ends_with(")0:0);\n"));
+}
+
+#[test]
+fn prune_dead_lineages() {
+	let mut tree = line();
+	let last = GENERATIONS - 1;
+	let parent = tree.get(10).cloned().unwrap();
+	tree.insert(GENERATIONS, Lineage::offspring_of(10, &parent, 11.));
+	tree.prune(&[5000].iter().cloned().collect::<BTreeSet<_>>());
+	assert_eq!(tree.len(), 5001);
+	assert!(!tree.contains(last) && !tree.contains(GENERATIONS));
+	assert_eq!(tree.descendants(0), GENERATIONS);
+	assert_eq!(tree.offspring(10), 2);
+}
@@ -0,0 +1,93 @@
+//! A replay must end in the same world as the recorded session, even if the
+//! configuration file has changed since
+extern crate rust_oids;
+
+use rust_oids::app::journal::{JournalWriter, Replay};
+use rust_oids::app::Event;
+use rust_oids::app::{App, RunOptions};
+use rust_oids::backend::config::ConfigSource;
+use rust_oids::backend::world::agent::AgentType;
+use rust_oids::core::clock::seconds;
+use rust_oids::core::geometry::Position;
+use rust_oids::core::resource::filesystem::{ResourceLoader, ResourceLoaderBuilder};
+use rust_oids::SystemMode;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const STEPS: usize = 240;
+
+fn resources() -> ResourceLoader {
+	ResourceLoaderBuilder::new().add(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")).build()
+}
+
+fn temp_file(name: &str) -> PathBuf { env::temp_dir().join(format!("rust-oids-replay-{}-{}", process::id(), name)) }
+
+fn new_app(seed: u64, options: RunOptions) -> App {
+	let mut app =
+		App::new(256, 256, 1., env::temp_dir(), &resources(), "minion_gene_pool.csv", None, Some(seed), None).unwrap();
+	app.set_options(options);
+	app.init(SystemMode::Batch);
+	app
+}
+
+/// Id, energy and position of every minion
+fn minions(app: &App) -> Vec<(usize, f32, f32, f32)> {
+	app.world()
+		.agents(AgentType::Minion)
+		.iter()
+		.map(|(id, agent)| {
+			let position = agent.segments[0].transform.position;
+			(*id, agent.state.energy(), position.x, position.y)
+		})
+		.collect()
+}
+
+#[test]
+fn replay_a_recorded_session() {
+	let journal = temp_file("session.jsonl");
+	let config = temp_file("config.json");
+	fs::write(&config, "{}").unwrap();
+	let mut config_source = ConfigSource::new(Some(config.clone()), Vec::new());
+	config_source.load().unwrap();
+
+	let recorded = {
+		let mut app = new_app(12345, RunOptions {
+			journal: Some(JournalWriter::create(&journal).unwrap()),
+			config_source: Some(config_source),
+			..Default::default()
+		});
+		for step in 0..STEPS {
+			match step {
+				30 => app.interact(Event::NewMinion(Position::new(10., 10.))),
+				60 => app.interact(Event::AddFeeder(Position::new(-20., 15.))),
+				90 => {
+					fs::write(&config, r#"{"growth_cost_ratio": 0.3, "collision_base_cost": 2.0}"#).unwrap();
+					app.interact(Event::Reload);
+				}
+				120 => app.interact(Event::RandomizeMinion(Position::new(-10., -10.))),
+				_ => {}
+			}
+			app.simulate(seconds(1. / 60.));
+		}
+		minions(&app)
+	};
+	// the replay must ignore the file, and apply the recorded configuration
+	fs::write(&config, r#"{"growth_cost_ratio": 0.01}"#).unwrap();
+
+	let replay = Replay::open(&journal).unwrap();
+	let mut app = new_app(replay.seed(), RunOptions { replay: Some(replay), ..Default::default() });
+	let mut steps = 0;
+	while !app.is_replay_finished() {
+		app.simulate(seconds(1.));
+		steps += 1;
+	}
+	fs::remove_file(&journal).ok();
+	fs::remove_file(&config).ok();
+
+	assert_eq!(steps, STEPS);
+	assert_eq!(app.world().config().growth_cost_ratio, 0.3);
+	assert!(!recorded.is_empty());
+	assert_eq!(minions(&app), recorded);
+}
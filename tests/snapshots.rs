@@ -0,0 +1,63 @@
+//! Snapshots must load back in every format, including those saved by older
+//! versions
+extern crate rust_oids;
+
+use rust_oids::backend::world::agent::AgentType;
+use rust_oids::backend::world::persist::SnapshotFormat;
+use rust_oids::core::clock::seconds;
+use rust_oids::core::resource::filesystem::{ResourceLoader, ResourceLoaderBuilder};
+use rust_oids::Simulation;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn fixture(file_name: &str) -> PathBuf { Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(file_name) }
+
+fn resources() -> ResourceLoader {
+	ResourceLoaderBuilder::new().add(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")).build()
+}
+
+fn load(snapshot: &Path) -> Simulation {
+	Simulation::from_snapshot(&resources(), "minion_gene_pool.csv", snapshot).expect("snapshot should load")
+}
+
+/// Id, energy and position of every minion
+fn minions(simulation: &Simulation) -> Vec<(usize, f32, f32, f32)> {
+	simulation
+		.agents(AgentType::Minion)
+		.iter()
+		.map(|(id, agent)| {
+			let position = agent.segments[0].transform.position;
+			(*id, agent.state.energy(), position.x, position.y)
+		})
+		.collect()
+}
+
+#[test]
+fn save_and_load_every_format() {
+	let mut simulation = Simulation::from_gene_pool(&resources(), "minion_gene_pool.csv", 12345);
+	simulation.run(seconds(1. / 60.), 60);
+	assert!(!simulation.world().family_tree().is_empty());
+	for format in SnapshotFormat::all() {
+		let path = env::temp_dir().join(format!("rust-oids-snapshot-{}.{}", process::id(), format.extension()));
+		simulation.save(&path).expect("snapshot should save");
+		let loaded = load(&path);
+		fs::remove_file(&path).ok();
+		assert_eq!(minions(&loaded), minions(&simulation), "{:?}", format);
+		// JSON may round the last digit of the clock
+		assert!((loaded.seconds().get() - simulation.seconds().get()).abs() < 1e-9, "{:?}", format);
+		assert_eq!(loaded.world().family_tree().len(), simulation.world().family_tree().len(), "{:?}", format);
+	}
+}
+
+#[test]
+fn load_version_1() {
+	let simulation = load(&fixture("snapshot_v1.json"));
+	let minions = simulation.agents(AgentType::Minion);
+	assert_eq!(minions.keys().cloned().collect::<Vec<_>>(), vec![256, 512]);
+	let position = minions[&256].segments[0].transform.position;
+	assert_eq!((position.x, position.y), (10., -20.));
+	assert_eq!(minions[&512].state.energy(), 60.);
+	assert_eq!(simulation.world().family_tree().len(), 2);
+}